@@ -5,7 +5,7 @@ use bindgen::{CodegenConfig, EnumVariation};
 use cc::Build;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::{env, fs};
+use std::{env, fs, io};
 
 /// The libraries to link with.
 mod lib {
@@ -13,6 +13,8 @@ mod lib {
     pub const SKIA_BINDINGS: &str = "skia-bindings";
     pub const SKSHAPER: &str = "skshaper";
     pub const SKPARAGRAPH: &str = "skparagraph";
+    pub const SKOTTIE: &str = "skottie";
+    pub const SKSG: &str = "sksg";
 }
 
 /// Feature identifiers define the additional configuration parts of the binaries to download.
@@ -21,6 +23,8 @@ mod feature_id {
     pub const VULKAN: &str = "vulkan";
     pub const METAL: &str = "metal";
     pub const TEXTLAYOUT: &str = "textlayout";
+    pub const ANIMATION: &str = "animation";
+    pub const SVG: &str = "svg";
 }
 
 /// The defaults for the Skia build configuration.
@@ -41,7 +45,8 @@ impl Default for BuildConfiguration {
                 vulkan: cfg!(feature = "vulkan"),
                 metal: cfg!(feature = "metal"),
                 text_layout: cfg!(feature = "textlayout"),
-                animation: false,
+                animation: cfg!(feature = "animation"),
+                svg: cfg!(feature = "svg"),
                 dng: false,
                 particles: false,
             },
@@ -80,9 +85,13 @@ pub struct Features {
     /// Features related to text layout. Modules skshaper and skparagraph.
     pub text_layout: bool,
 
-    /// Build with animation support (yet unsupported, no wrappers).
+    /// Build with animation support. Modules skottie, sksg and skshaper.
     pub animation: bool,
 
+    /// Build with the SVG DOM. The SVG model is not part of the Skia library, so it is compiled
+    /// with the bindings.
+    pub svg: bool,
+
     /// Support DNG file format (currently unsupported because of build errors).
     pub dng: bool,
 
@@ -111,6 +120,12 @@ impl Features {
         if self.text_layout {
             feature_ids.push(feature_id::TEXTLAYOUT);
         }
+        if self.animation {
+            feature_ids.push(feature_id::ANIMATION);
+        }
+        if self.svg {
+            feature_ids.push(feature_id::SVG);
+        }
 
         feature_ids
     }
@@ -131,6 +146,9 @@ pub struct FinalBuildConfiguration {
 
     /// The binding source files to compile.
     pub binding_sources: Vec<PathBuf>,
+
+    /// Compile the SVG model with the bindings?
+    pub svg_model: bool,
 }
 
 impl FinalBuildConfiguration {
@@ -153,7 +171,9 @@ impl FinalBuildConfiguration {
                 ("skia_use_gl", if features.gl { yes() } else { no() }),
                 ("skia_use_system_libjpeg_turbo", no()),
                 ("skia_use_system_libpng", no()),
-                ("skia_use_libwebp", no()),
+                // libwebp decodes (animated) WebP images and backs SkWebpEncoder.
+                ("skia_use_libwebp", yes()),
+                ("skia_use_system_libwebp", no()),
                 ("skia_use_system_zlib", no()),
                 ("skia_use_xps", no()),
                 ("skia_use_dng_sdk", if features.dng { yes() } else { no() }),
@@ -180,9 +200,13 @@ impl FinalBuildConfiguration {
                 args.push(("skia_use_lua", no()));
             }
 
+            // Skottie shapes text layers with skshaper.
+            if features.text_layout || features.animation {
+                args.push(("skia_enable_skshaper", yes()));
+            }
+
             if features.text_layout {
                 args.extend(vec![
-                    ("skia_enable_skshaper", yes()),
                     ("skia_use_icu", yes()),
                     ("skia_use_system_icu", no()),
                     ("skia_use_harfbuzz", yes()),
@@ -198,6 +222,10 @@ impl FinalBuildConfiguration {
                 args.push(("skia_use_icu", no()));
             }
 
+            if features.animation {
+                args.push(("skia_enable_skottie", yes()));
+            }
+
             let mut flags: Vec<&str> = vec![];
             let mut use_expat = true;
 
@@ -277,6 +305,12 @@ impl FinalBuildConfiguration {
                     "obj/modules/skparagraph/skparagraph.ninja".into(),
                 ]);
             }
+            if features.animation {
+                files.extend(vec![
+                    "obj/modules/skottie/skottie.ninja".into(),
+                    "obj/modules/sksg/sksg.ninja".into(),
+                ]);
+            }
             files
         };
 
@@ -298,7 +332,13 @@ impl FinalBuildConfiguration {
             if features.text_layout {
                 sources.extend(vec!["src/shaper.cpp".into(), "src/paragraph.cpp".into()]);
             }
+            if features.animation {
+                sources.push("src/skottie.cpp".into());
+            }
             sources.push("src/svg.cpp".into());
+            if features.svg {
+                sources.push("src/svg_dom.cpp".into());
+            }
             sources
         };
 
@@ -307,6 +347,7 @@ impl FinalBuildConfiguration {
             ninja_files,
             definitions: build.definitions.clone(),
             binding_sources,
+            svg_model: features.svg,
         }
     }
 }
@@ -361,6 +402,14 @@ impl BinariesConfiguration {
             built_libraries.push(lib::SKSHAPER.into());
         }
 
+        if features.animation {
+            built_libraries.push(lib::SKOTTIE.into());
+            built_libraries.push(lib::SKSG.into());
+            if !features.text_layout {
+                built_libraries.push(lib::SKSHAPER.into());
+            }
+        }
+
         let mut link_libraries = Vec::new();
 
         match target.as_strs() {
@@ -620,7 +669,19 @@ fn bindgen_gen(build: &FinalBuildConfiguration, current_dir: &Path, output_direc
     cargo::rerun_if_changed(include_path.join("include"));
 
     builder = builder.clang_arg(format!("-I{}", include_path.display()));
-    cc_build.include(include_path);
+    cc_build.include(&include_path);
+
+    if build.svg_model {
+        let svg_model_path = include_path.join("experimental").join("svg").join("model");
+        let sources = cpp_sources(&svg_model_path).unwrap_or_else(|e| {
+            panic!(
+                "failed to list the SVG model sources in {}: {}",
+                svg_model_path.display(),
+                e
+            )
+        });
+        cc_build.files(sources);
+    }
 
     let definitions = {
         let mut definitions = Vec::new();
@@ -689,6 +750,18 @@ fn bindgen_gen(build: &FinalBuildConfiguration, current_dir: &Path, output_direc
         .expect("Couldn't write bindings!");
 }
 
+/// Returns the C++ source files in `dir`.
+fn cpp_sources(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut sources = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let source = entry?.path();
+        if source.extension().and_then(|e| e.to_str()) == Some("cpp") {
+            sources.push(source);
+        }
+    }
+    Ok(sources)
+}
+
 const WHITELISTED_FUNCTIONS: &[&str] = &[
     "SkAnnotateRectWithURL",
     "SkAnnotateNamedDestination",
@@ -772,6 +845,7 @@ const OPAQUE_TYPES: &[&str] = &[
     "Sk1DPathEffect",
     "SkBBoxHierarchy", // vtable
     "SkBBHFactory",
+    "SkRTreeFactory",
     "SkBitmap_Allocator",
     "SkBitmap_HeapAllocator",
     "SkColorFilter",
@@ -819,6 +893,13 @@ const OPAQUE_TYPES: &[&str] = &[
     "GrShaderCaps",
     // m81: yet experimental
     "SkM44",
+    // experimental/svg/model
+    "SkSVGDOM",
+    // modules/skottie
+    "skottie::Animation",
+    "skottie::PropertyObserver",
+    "skottie::ResourceProvider",
+    "Skottie.*PropertyHandle",
     // more stuff we don't need that was tracked down fixing:
     // https://github.com/rust-skia/rust-skia/issues/318
     // referred from SkPath, but not used:
@@ -901,6 +982,16 @@ const ENUM_TABLE: &[EnumEntry] = &[
     ("Join", rewrite::k_xxx_name),
     // SkStrokeRec_InitStyle
     ("InitStyle", rewrite::k_xxx_name),
+    // SkCodec_ZeroInitialized
+    ("ZeroInitialized", rewrite::k_xxx_name),
+    // SkCodecAnimation_DisposalMethod
+    ("DisposalMethod", rewrite::k_xxx),
+    // SkJpegEncoder_AlphaOption
+    ("AlphaOption", rewrite::k_xxx),
+    // SkWebpEncoder_Compression
+    ("Compression", rewrite::k_xxx),
+    // SkCodec_SkScanlineOrder
+    ("SkScanlineOrder", rewrite::k_xxx_name),
     // SkBlurImageFilter_TileMode
     // SkMatrixConvolutionImageFilter_TileMode
     ("TileMode", rewrite::k_xxx_name),
@@ -945,6 +1036,7 @@ const ENUM_TABLE: &[EnumEntry] = &[
     // SkSurface_*
     ("ContentChangeMode", rewrite::k_xxx_name),
     ("BackendHandleAccess", rewrite::k_xxx_name),
+    ("RescaleGamma", rewrite::k_xxx),
     // SkTextUtils_Align
     ("Align", rewrite::k_xxx_name),
     // SkTrimPathEffect_Mode
@@ -960,6 +1052,11 @@ const ENUM_TABLE: &[EnumEntry] = &[
     // private type that leaks through SkRuntimeEffect_Variable
     ("GrSLType", rewrite::k_xxx_name),
     //
+    // docs/
+    //
+    // SkPDF_DocumentStructureType
+    ("DocumentStructureType", rewrite::k_xxx),
+    //
     // gpu/
     //
     ("GrGLStandard", rewrite::k_xxx_name),
@@ -1,3 +1,4 @@
+#include <algorithm>
 #include <cassert>
 #include <tuple>
 
@@ -5,8 +6,10 @@
 // codec/
 #include "include/codec/SkEncodedOrigin.h"
 #include "include/codec/SkCodec.h"
+#include "include/codec/SkAndroidCodec.h"
 // core/
 #include "include/core/SkAnnotation.h"
+#include "include/core/SkBBHFactory.h"
 #include "include/core/SkBlendMode.h"
 #include "include/core/SkCanvas.h"
 #include "include/core/SkColor.h"
@@ -39,11 +42,13 @@
 #include "include/core/SkPixelRef.h"
 #include "include/core/SkPoint.h"
 #include "include/core/SkPoint3.h"
+#include "include/core/SkRasterHandleAllocator.h"
 #include "include/core/SkRect.h"
 #include "include/core/SkRefCnt.h"
 #include "include/core/SkRegion.h"
 #include "include/core/SkRRect.h"
 #include "include/core/SkRSXform.h"
+#include "include/core/SkSerialProcs.h"
 #include "include/core/SkStream.h"
 #include "include/core/SkStrokeRec.h"
 #include "include/core/SkSurface.h"
@@ -56,6 +61,10 @@
 #include "include/core/SkYUVASizeInfo.h"
 // docs/
 #include "include/docs/SkPDFDocument.h"
+// encode/
+#include "include/encode/SkJpegEncoder.h"
+#include "include/encode/SkPngEncoder.h"
+#include "include/encode/SkWebpEncoder.h"
 // effects/
 #include "include/effects/Sk1DPathEffect.h"
 #include "include/effects/Sk2DPathEffect.h"
@@ -91,6 +100,7 @@
 
 #include "include/effects/SkRuntimeEffect.h"
 #include "src/sksl/SkSLByteCode.h"
+#include "src/image/SkImage_Base.h"
 
 #include "include/effects/SkPerlinNoiseShader.h"
 #include "include/effects/SkShaderMaskFilter.h"
@@ -107,9 +117,15 @@
 #include "include/utils/SkCamera.h"
 #include "include/utils/SkInterpolator.h"
 #include "include/utils/SkNullCanvas.h"
+#include "include/utils/SkNWayCanvas.h"
+#include "include/utils/SkPaintFilterCanvas.h"
 #include "include/utils/SkParsePath.h"
 #include "include/utils/SkShadowUtils.h"
 #include "include/utils/SkTextUtils.h"
+#include "src/core/SkCanvasPriv.h"
+#include "src/core/SkDrawShadowInfo.h"
+#include "src/core/SkYUVMath.h"
+#include "src/utils/SkMultiPictureDocument.h"
 
 //
 // codec/SkCodec.h
@@ -119,6 +135,10 @@ extern "C" SkCodec* C_SkCodec_MakeFromData(SkData* data) {
     return SkCodec::MakeFromData(sp(data)).release();
 }
 
+extern "C" SkCodec* C_SkCodec_MakeFromStream(SkStream* stream, SkCodec::Result* result) {
+    return SkCodec::MakeFromStream(std::unique_ptr<SkStream>(stream), result).release();
+}
+
 extern "C" void C_SkCodec_getInfo(const SkCodec* self, SkImageInfo* info) {
     *info = self->getInfo();
 }
@@ -139,6 +159,88 @@ extern "C" SkEncodedImageFormat C_SkCodec_getEncodedFormat(const SkCodec* self)
     return self->getEncodedFormat();
 }
 
+extern "C" void C_SkCodec_delete(SkCodec* self) {
+    delete self;
+}
+
+extern "C" bool C_SkCodec_getValidSubset(const SkCodec* self, SkIRect* desiredSubset) {
+    return self->getValidSubset(desiredSubset);
+}
+
+extern "C" SkCodec::Result C_SkCodec_incrementalDecode(SkCodec* self, int* rowsDecoded) {
+    return self->incrementalDecode(rowsDecoded);
+}
+
+extern "C" SkCodec::SkScanlineOrder C_SkCodec_getScanlineOrder(const SkCodec* self) {
+    return self->getScanlineOrder();
+}
+
+extern "C" int C_SkCodec_nextScanline(const SkCodec* self) {
+    return self->nextScanline();
+}
+
+extern "C" int C_SkCodec_getFrameCount(SkCodec* self) {
+    return self->getFrameCount();
+}
+
+extern "C" bool C_SkCodec_getFrameInfo(const SkCodec* self, int index, SkCodec::FrameInfo* info) {
+    return self->getFrameInfo(index, info);
+}
+
+extern "C" int C_SkCodec_getRepetitionCount(SkCodec* self) {
+    return self->getRepetitionCount();
+}
+
+//
+// codec/SkAndroidCodec.h
+//
+
+extern "C" SkAndroidCodec* C_SkAndroidCodec_MakeFromData(SkData* data) {
+    return SkAndroidCodec::MakeFromData(sp(data)).release();
+}
+
+extern "C" SkAndroidCodec* C_SkAndroidCodec_MakeFromStream(SkStream* stream) {
+    return SkAndroidCodec::MakeFromStream(std::unique_ptr<SkStream>(stream)).release();
+}
+
+extern "C" SkAndroidCodec* C_SkAndroidCodec_MakeFromCodec(SkCodec* codec) {
+    return SkAndroidCodec::MakeFromCodec(std::unique_ptr<SkCodec>(codec)).release();
+}
+
+extern "C" void C_SkAndroidCodec_delete(SkAndroidCodec* self) {
+    delete self;
+}
+
+extern "C" void C_SkAndroidCodec_getInfo(const SkAndroidCodec* self, SkImageInfo* info) {
+    *info = self->getInfo();
+}
+
+extern "C" SkEncodedImageFormat C_SkAndroidCodec_getEncodedFormat(const SkAndroidCodec* self) {
+    return self->getEncodedFormat();
+}
+
+extern "C" SkColorSpace* C_SkAndroidCodec_computeOutputColorSpace(SkAndroidCodec* self, SkColorType outputColorType, SkColorSpace* prefColorSpace) {
+    return self->computeOutputColorSpace(outputColorType, sp(prefColorSpace)).release();
+}
+
+extern "C" SkISize C_SkAndroidCodec_getSampledDimensions(const SkAndroidCodec* self, int sampleSize) {
+    return self->getSampledDimensions(sampleSize);
+}
+
+extern "C" SkISize C_SkAndroidCodec_getSampledSubsetDimensions(const SkAndroidCodec* self, int sampleSize, const SkIRect* subset) {
+    return self->getSampledSubsetDimensions(sampleSize, *subset);
+}
+
+extern "C" SkCodec::Result C_SkAndroidCodec_getAndroidPixels(
+        SkAndroidCodec* self, const SkImageInfo* info, void* pixels, size_t rowBytes,
+        SkCodec::ZeroInitialized zeroInitialized, SkIRect* subset, int sampleSize) {
+    SkAndroidCodec::AndroidOptions options;
+    options.fZeroInitialized = zeroInitialized;
+    options.fSubset = subset;
+    options.fSampleSize = sampleSize;
+    return self->getAndroidPixels(*info, pixels, rowBytes, &options);
+}
+
 //
 // codec/SkEncodedOrigin.h
 //
@@ -161,6 +263,18 @@ extern "C" SkSurface* C_SkSurface_MakeRasterDirect(const SkImageInfo* imageInfo,
     return SkSurface::MakeRasterDirect(*imageInfo, pixels, rowBytes, surfaceProps).release();
 }
 
+extern "C" SkSurface* C_SkSurface_MakeRasterDirectReleaseProc(
+        const SkImageInfo* imageInfo, void* pixels, size_t rowBytes,
+        void (*releaseProc)(void* pixels, void* context), void* context,
+        const SkSurfaceProps* surfaceProps) {
+    auto surface = SkSurface::MakeRasterDirectReleaseProc(*imageInfo, pixels, rowBytes, releaseProc, context, surfaceProps);
+    // MakeRasterDirectReleaseProc does not call the release proc if the surface can not be created.
+    if (!surface && releaseProc) {
+        releaseProc(pixels, context);
+    }
+    return surface.release();
+}
+
 extern "C" SkSurface* C_SkSurface_MakeRaster(const SkImageInfo* imageInfo, size_t rowBytes, const SkSurfaceProps* surfaceProps) {
     return SkSurface::MakeRaster(*imageInfo, rowBytes, surfaceProps).release();
 }
@@ -209,6 +323,134 @@ extern "C" const SkSurfaceProps* C_SkSurface_props(const SkSurface* self) {
     return &self->props();
 }
 
+namespace AsyncReadPixels {
+    extern "C" typedef void (*Callback)(void* context, const SkSurface::AsyncReadResult* result);
+}
+
+// Forwards the results of the asynchronous reads to a callback that takes ownership of the result.
+struct AsyncReadPixelsContext {
+    AsyncReadPixels::Callback callback;
+    void* context;
+
+    static void ReadPixelsCallback(void* readPixelsContext, std::unique_ptr<const SkSurface::AsyncReadResult> result) {
+        auto self = static_cast<AsyncReadPixelsContext*>(readPixelsContext);
+        self->callback(self->context, result.release());
+        delete self;
+    }
+};
+
+extern "C" void C_SkSurface_asyncRescaleAndReadPixels(
+        SkSurface* self,
+        const SkImageInfo* info, const SkIRect* srcRect,
+        SkSurface::RescaleGamma rescaleGamma, SkFilterQuality rescaleQuality,
+        AsyncReadPixels::Callback callback, void* context) {
+    self->asyncRescaleAndReadPixels(
+            *info, *srcRect, rescaleGamma, rescaleQuality,
+            AsyncReadPixelsContext::ReadPixelsCallback, new AsyncReadPixelsContext { callback, context });
+}
+
+// The Y, U and V planes of the raster fallback for asyncRescaleAndReadPixelsYUV420().
+class YUV420ReadResult : public SkSurface::AsyncReadResult {
+public:
+    int count() const override {
+        return 3;
+    }
+
+    const void* data(int i) const override {
+        return planes[i].getPixels();
+    }
+
+    size_t rowBytes(int i) const override {
+        return planes[i].rowBytes();
+    }
+
+    SkBitmap planes[3];
+};
+
+// Converts rescaled RGBA pixels to Y, U and V planes with Skia's RGB to YUV color matrix. The
+// U and V planes are downsampled by drawing them at half the size.
+static std::unique_ptr<const SkSurface::AsyncReadResult> convertToYUV420(
+        const SkSurface::AsyncReadResult& rgba, const SkImageInfo& rgbaInfo, SkYUVColorSpace yuvColorSpace) {
+    auto image = SkImage::MakeFromRaster(SkPixmap(rgbaInfo, rgba.data(0), rgba.rowBytes(0)), nullptr, nullptr);
+    if (!image) {
+        return nullptr;
+    }
+    float rgbToYuv[20];
+    SkColorMatrix_RGB2YUV(yuvColorSpace, rgbToYuv);
+
+    auto result = std::unique_ptr<YUV420ReadResult>(new YUV420ReadResult());
+    auto chromaSize = SkISize::Make((rgbaInfo.width() + 1) / 2, (rgbaInfo.height() + 1) / 2);
+    for (int i = 0; i < 3; ++i) {
+        auto& plane = result->planes[i];
+        if (!plane.tryAllocPixels(SkImageInfo::MakeA8(i == 0 ? rgbaInfo.dimensions() : chromaSize))) {
+            return nullptr;
+        }
+        // moves the row of the plane's component into alpha.
+        float matrix[20] = {};
+        std::copy(rgbToYuv + i * 5, rgbToYuv + i * 5 + 5, matrix + 15);
+        SkPaint paint;
+        paint.setColorFilter(SkColorFilters::Matrix(matrix));
+        paint.setBlendMode(SkBlendMode::kSrc);
+        paint.setFilterQuality(kLow_SkFilterQuality);
+        SkCanvas canvas(plane);
+        canvas.drawImageRect(image, SkRect::Make(plane.dimensions()), &paint);
+    }
+    return result;
+}
+
+// Reads the rescaled RGBA pixels first and forwards them converted to YUV420.
+struct AsyncReadYUV420Context {
+    SkImageInfo rgbaInfo;
+    SkYUVColorSpace yuvColorSpace;
+    AsyncReadPixelsContext* context;
+
+    static void ReadPixelsCallback(void* readPixelsContext, std::unique_ptr<const SkSurface::AsyncReadResult> rgba) {
+        auto self = static_cast<AsyncReadYUV420Context*>(readPixelsContext);
+        AsyncReadPixelsContext::ReadPixelsCallback(
+                self->context,
+                rgba ? convertToYUV420(*rgba, self->rgbaInfo, self->yuvColorSpace) : nullptr);
+        delete self;
+    }
+};
+
+extern "C" void C_SkSurface_asyncRescaleAndReadPixelsYUV420(
+        SkSurface* self,
+        SkYUVColorSpace yuvColorSpace, SkColorSpace* dstColorSpace,
+        const SkIRect* srcRect, const SkISize* dstSize,
+        SkSurface::RescaleGamma rescaleGamma, SkFilterQuality rescaleQuality,
+        AsyncReadPixels::Callback callback, void* context) {
+    auto readPixelsContext = new AsyncReadPixelsContext { callback, context };
+    // m81 implements the YUV420 read only for GPU surfaces, raster surfaces call back with
+    // nullptr.
+    if (!self->getCanvas()->getGrContext()) {
+        auto rgbaInfo = SkImageInfo::MakeN32Premul(*dstSize, sp(dstColorSpace));
+        self->asyncRescaleAndReadPixels(
+                rgbaInfo, *srcRect, rescaleGamma, rescaleQuality,
+                AsyncReadYUV420Context::ReadPixelsCallback,
+                new AsyncReadYUV420Context { rgbaInfo, yuvColorSpace, readPixelsContext });
+        return;
+    }
+    self->asyncRescaleAndReadPixelsYUV420(
+            yuvColorSpace, sp(dstColorSpace), *srcRect, *dstSize, rescaleGamma, rescaleQuality,
+            AsyncReadPixelsContext::ReadPixelsCallback, readPixelsContext);
+}
+
+extern "C" void C_SkSurface_AsyncReadResult_delete(const SkSurface::AsyncReadResult* self) {
+    delete self;
+}
+
+extern "C" int C_SkSurface_AsyncReadResult_count(const SkSurface::AsyncReadResult* self) {
+    return self->count();
+}
+
+extern "C" const void* C_SkSurface_AsyncReadResult_data(const SkSurface::AsyncReadResult* self, int i) {
+    return self->data(i);
+}
+
+extern "C" size_t C_SkSurface_AsyncReadResult_rowBytes(const SkSurface::AsyncReadResult* self, int i) {
+    return self->rowBytes(i);
+}
+
 //
 // core/SkSurfaceCharacterization.h
 //
@@ -246,6 +488,15 @@ extern "C" SkImage* C_SkImage_MakeRasterData(const SkImageInfo* info, SkData* pi
     return SkImage::MakeRasterData(*info, sp(pixels), rowBytes).release();
 }
 
+extern "C" SkImage* C_SkImage_MakeFromRaster(const SkPixmap* pixmap, SkImage::RasterReleaseProc rasterReleaseProc, SkImage::ReleaseContext releaseContext) {
+    auto image = SkImage::MakeFromRaster(*pixmap, rasterReleaseProc, releaseContext);
+    // MakeFromRaster does not call the release proc if the image can not be created.
+    if (!image && rasterReleaseProc) {
+        rasterReleaseProc(pixmap->addr(), releaseContext);
+    }
+    return image.release();
+}
+
 extern "C" SkImage* C_SkImage_MakeFromBitmap(const SkBitmap* bitmap) {
     return SkImage::MakeFromBitmap(*bitmap).release();
 }
@@ -312,6 +563,64 @@ extern "C" SkImage* C_SkImage_reinterpretColorSpace(const SkImage* self, SkColor
     return self->reinterpretColorSpace(sp(newColorSpace)).release();
 }
 
+// SkImage does not support asynchronous reads yet, so the source rectangle of the image is drawn
+// to a surface that is created on the image's GrContext, and the surface is read from instead.
+static sk_sp<SkSurface> makeAsyncReadSurface(const SkImage* image, const SkIRect& srcRect) {
+    if (!image->bounds().contains(srcRect)) {
+        return nullptr;
+    }
+    auto info = image->imageInfo().makeDimensions(srcRect.size());
+    auto context = as_IB(image)->context();
+    auto makeSurface = [&](const SkImageInfo& info) {
+        return context
+            ? SkSurface::MakeRenderTarget(context, SkBudgeted::kYes, info)
+            : SkSurface::MakeRaster(info);
+    };
+    auto surface = makeSurface(info);
+    if (!surface) {
+        surface = makeSurface(info.makeColorType(kN32_SkColorType));
+    }
+    if (surface) {
+        SkPaint paint;
+        paint.setBlendMode(SkBlendMode::kSrc);
+        surface->getCanvas()->drawImage(image, -srcRect.x(), -srcRect.y(), &paint);
+    }
+    return surface;
+}
+
+extern "C" void C_SkImage_asyncRescaleAndReadPixels(
+        const SkImage* self,
+        const SkImageInfo* info, const SkIRect* srcRect,
+        SkSurface::RescaleGamma rescaleGamma, SkFilterQuality rescaleQuality,
+        AsyncReadPixels::Callback callback, void* context) {
+    auto surface = makeAsyncReadSurface(self, *srcRect);
+    if (!surface) {
+        callback(context, nullptr);
+        return;
+    }
+    auto surfaceRect = SkIRect::MakeSize(srcRect->size());
+    C_SkSurface_asyncRescaleAndReadPixels(
+            surface.get(), info, &surfaceRect, rescaleGamma, rescaleQuality, callback, context);
+}
+
+extern "C" void C_SkImage_asyncRescaleAndReadPixelsYUV420(
+        const SkImage* self,
+        SkYUVColorSpace yuvColorSpace, SkColorSpace* dstColorSpace,
+        const SkIRect* srcRect, const SkISize* dstSize,
+        SkSurface::RescaleGamma rescaleGamma, SkFilterQuality rescaleQuality,
+        AsyncReadPixels::Callback callback, void* context) {
+    auto surface = makeAsyncReadSurface(self, *srcRect);
+    if (!surface) {
+        SkSafeUnref(dstColorSpace);
+        callback(context, nullptr);
+        return;
+    }
+    auto surfaceRect = SkIRect::MakeSize(srcRect->size());
+    C_SkSurface_asyncRescaleAndReadPixelsYUV420(
+            surface.get(), yuvColorSpace, dstColorSpace, &surfaceRect, dstSize,
+            rescaleGamma, rescaleQuality, callback, context);
+}
+
 //
 // core/SkImageEncoder.h
 //
@@ -324,6 +633,59 @@ extern "C" SkData *C_SkEncodeBitmap(const SkBitmap *src, SkEncodedImageFormat fo
     return SkEncodeBitmap(*src, format, quality).release();
 }
 
+extern "C" bool C_SkEncodeImage(SkWStream* dst, const SkPixmap* src, SkEncodedImageFormat format, int quality) {
+    return SkEncodeImage(dst, *src, format, quality);
+}
+
+//
+// encode/SkPngEncoder.h
+//
+
+extern "C" bool C_SkPngEncoder_Encode(
+        SkWStream* dst, const SkPixmap* src,
+        int filterFlags, int zLibLevel,
+        const char* const* comments, size_t commentsCount) {
+    SkPngEncoder::Options options;
+    options.fFilterFlags = static_cast<SkPngEncoder::FilterFlag>(filterFlags);
+    options.fZLibLevel = zLibLevel;
+    if (commentsCount) {
+        std::vector<size_t> sizes;
+        for (size_t i = 0; i != commentsCount; ++i) {
+            sizes.push_back(strlen(comments[i]) + 1);
+        }
+        options.fComments = SkDataTable::MakeCopyArrays(
+                reinterpret_cast<const void* const*>(comments), sizes.data(), commentsCount);
+    }
+    return SkPngEncoder::Encode(dst, *src, options);
+}
+
+//
+// encode/SkJpegEncoder.h
+//
+
+extern "C" bool C_SkJpegEncoder_Encode(
+        SkWStream* dst, const SkPixmap* src,
+        int quality, SkJpegEncoder::Downsample downsample, SkJpegEncoder::AlphaOption alphaOption) {
+    SkJpegEncoder::Options options;
+    options.fQuality = quality;
+    options.fDownsample = downsample;
+    options.fAlphaOption = alphaOption;
+    return SkJpegEncoder::Encode(dst, *src, options);
+}
+
+//
+// encode/SkWebpEncoder.h
+//
+
+extern "C" bool C_SkWebpEncoder_Encode(
+        SkWStream* dst, const SkPixmap* src,
+        SkWebpEncoder::Compression compression, float quality) {
+    SkWebpEncoder::Options options;
+    options.fCompression = compression;
+    options.fQuality = quality;
+    return SkWebpEncoder::Encode(dst, *src, options);
+}
+
 //
 // core/SkData.h
 //
@@ -364,6 +726,10 @@ extern "C" SkData* C_SkData_MakeEmpty() {
     return SkData::MakeEmpty().release();
 }
 
+extern "C" SkData* C_SkData_MakeFromStream(SkStream* stream, size_t size) {
+    return SkData::MakeFromStream(stream, size).release();
+}
+
 //
 // core/SkPaint.h
 //
@@ -564,10 +930,391 @@ extern "C" void C_SkCanvas_getTotalMatrix(const SkCanvas* self, SkMatrix* matrix
     *matrix = self->getTotalMatrix();
 }
 
+// The 4x4 matrix functions of SkCanvas take column major scalars.
+
+extern "C" void C_SkCanvas_concat44(SkCanvas* self, const SkMatrix44* matrix) {
+    SkScalar colMajor[16];
+    matrix->asColMajorf(colMajor);
+    self->concat44(colMajor);
+}
+
+extern "C" void C_SkCanvas_setMatrix44(SkCanvas* self, const SkMatrix44* matrix) {
+    self->resetMatrix();
+    C_SkCanvas_concat44(self, matrix);
+}
+
+extern "C" void C_SkCanvas_getLocalToDevice(const SkCanvas* self, SkMatrix44* matrix) {
+    SkScalar colMajor[16];
+    self->getLocalToDevice(colMajor);
+    matrix->setColMajorf(colMajor);
+}
+
 extern "C" void C_SkCanvas_discard(SkCanvas* self) {
     self->discard();
 }
 
+extern "C" void C_SkCanvas_drawAtlas(
+        SkCanvas* self, const SkImage* atlas,
+        const SkRSXform xform[], const SkRect tex[], const SkColor colors[], int count,
+        SkBlendMode mode, const SkRect* cullRect, const SkPaint* paint) {
+    self->drawAtlas(atlas, xform, tex, colors, count, mode, cullRect, paint);
+}
+
+extern "C" void C_SkCanvas_experimental_DrawEdgeAAQuad(
+        SkCanvas* self, const SkRect* rect, const SkPoint clip[4], SkCanvas::QuadAAFlags aaFlags,
+        const SkColor4f* color, SkBlendMode mode) {
+    self->experimental_DrawEdgeAAQuad(*rect, clip, aaFlags, *color, mode);
+}
+
+// An SkCanvas::ImageSetEntry that borrows its image.
+struct ImageSetEntryRef {
+    const SkImage* image;
+    SkRect srcRect;
+    SkRect dstRect;
+    int matrixIndex;
+    float alpha;
+    unsigned aaFlags;
+    bool hasClip;
+};
+
+extern "C" void C_SkCanvas_experimental_DrawEdgeAAImageSet(
+        SkCanvas* self, const ImageSetEntryRef imageSet[], int count,
+        const SkPoint dstClips[], const SkMatrix preViewMatrices[],
+        const SkPaint* paint, SkCanvas::SrcRectConstraint constraint) {
+    std::vector<SkCanvas::ImageSetEntry> entries;
+    entries.reserve(count);
+    for (int i = 0; i < count; ++i) {
+        auto& entry = imageSet[i];
+        entries.emplace_back(
+                sk_ref_sp(entry.image), entry.srcRect, entry.dstRect, entry.matrixIndex,
+                entry.alpha, entry.aaFlags, entry.hasClip);
+    }
+    self->experimental_DrawEdgeAAImageSet(entries.data(), count, dstClips, preViewMatrices, paint, constraint);
+}
+
+extern "C" void C_SkCanvas_drawBehind(SkCanvas* self, const SkPaint* paint) {
+    SkCanvasPriv::DrawBehind(self, *paint);
+}
+
+extern "C" void C_SkCanvas_private_draw_shadow_rec(SkCanvas* self, const SkPath* path, const SkPoint3* zPlaneParams, const SkPoint3* lightPos, SkScalar lightRadius, SkColor ambientColor, SkColor spotColor, uint32_t flags) {
+    SkDrawShadowRec rec;
+    rec.fZPlaneParams = *zPlaneParams;
+    rec.fLightPos = *lightPos;
+    rec.fLightRadius = lightRadius;
+    rec.fAmbientColor = ambientColor;
+    rec.fSpotColor = spotColor;
+    rec.fFlags = flags;
+    self->private_draw_shadow_rec(*path, rec);
+}
+
+namespace Canvas {
+    extern "C" typedef void (*Drop)(TraitObject);
+    extern "C" typedef void (*WillSave)(TraitObject);
+    extern "C" typedef void (*WillSaveLayer)(TraitObject, const SkRect* bounds, const SkPaint* paint, SkCanvas::SaveLayerFlags flags);
+    extern "C" typedef void (*WillRestore)(TraitObject);
+    extern "C" typedef void (*DidConcat)(TraitObject, const SkMatrix*);
+    extern "C" typedef void (*DidSetMatrix)(TraitObject, const SkMatrix*);
+    extern "C" typedef void (*DidConcat44)(TraitObject, const SkMatrix44*);
+    extern "C" typedef void (*DrawPaint)(TraitObject, const SkPaint*);
+    extern "C" typedef void (*DrawRect)(TraitObject, const SkRect*, const SkPaint*);
+    extern "C" typedef void (*DrawRRect)(TraitObject, const SkRRect*, const SkPaint*);
+    extern "C" typedef void (*DrawDRRect)(TraitObject, const SkRRect* outer, const SkRRect* inner, const SkPaint*);
+    extern "C" typedef void (*DrawOval)(TraitObject, const SkRect*, const SkPaint*);
+    extern "C" typedef void (*DrawArc)(TraitObject, const SkRect*, SkScalar startAngle, SkScalar sweepAngle, bool useCenter, const SkPaint*);
+    extern "C" typedef void (*DrawPath)(TraitObject, const SkPath*, const SkPaint*);
+    extern "C" typedef void (*DrawRegion)(TraitObject, const SkRegion*, const SkPaint*);
+    extern "C" typedef void (*DrawPoints)(TraitObject, SkCanvas::PointMode, size_t count, const SkPoint pts[], const SkPaint*);
+    extern "C" typedef void (*DrawTextBlob)(TraitObject, const SkTextBlob*, SkScalar x, SkScalar y, const SkPaint*);
+    extern "C" typedef void (*DrawImage)(TraitObject, const SkImage*, SkScalar left, SkScalar top, const SkPaint*);
+    extern "C" typedef void (*DrawImageRect)(TraitObject, const SkImage*, const SkRect* src, const SkRect* dst, const SkPaint*, SkCanvas::SrcRectConstraint);
+    extern "C" typedef void (*DrawImageNine)(TraitObject, const SkImage*, const SkIRect* center, const SkRect* dst, const SkPaint*);
+    extern "C" typedef void (*DrawImageLattice)(TraitObject, const SkImage*, const SkCanvas::Lattice*, const SkRect* dst, const SkPaint*);
+    extern "C" typedef void (*DrawVertices)(TraitObject, const SkVertices*, const SkVertices::Bone bones[], int boneCount, SkBlendMode, const SkPaint*);
+    extern "C" typedef void (*DrawPatch)(TraitObject, const SkPoint cubics[12], const SkColor colors[4], const SkPoint texCoords[4], SkBlendMode, const SkPaint*);
+    extern "C" typedef void (*DrawAtlas)(TraitObject, const SkImage*, const SkRSXform xform[], const SkRect tex[], const SkColor colors[], int count, SkBlendMode, const SkRect* cullRect, const SkPaint*);
+    extern "C" typedef void (*DrawAnnotation)(TraitObject, const SkRect*, const char key[], SkData* value);
+    extern "C" typedef void (*DrawShadowRec)(TraitObject, const SkPath*, const SkPoint3* zPlaneParams, const SkPoint3* lightPos, SkScalar lightRadius, SkColor ambientColor, SkColor spotColor, uint32_t flags);
+    extern "C" typedef void (*DrawEdgeAAQuad)(TraitObject, const SkRect*, const SkPoint clip[4], SkCanvas::QuadAAFlags, const SkColor4f*, SkBlendMode);
+    extern "C" typedef void (*DrawEdgeAAImageSet)(TraitObject, const SkCanvas::ImageSetEntry imageSet[], int count, const SkPoint dstClips[], int dstClipCount, const SkMatrix preViewMatrices[], int matrixCount, const SkPaint*, SkCanvas::SrcRectConstraint);
+    extern "C" typedef void (*DrawBehind)(TraitObject, const SkPaint*);
+    extern "C" typedef void (*ClipRect)(TraitObject, const SkRect*, SkClipOp, bool doAntiAlias);
+    extern "C" typedef void (*ClipRRect)(TraitObject, const SkRRect*, SkClipOp, bool doAntiAlias);
+    extern "C" typedef void (*ClipPath)(TraitObject, const SkPath*, SkClipOp, bool doAntiAlias);
+    extern "C" typedef void (*ClipRegion)(TraitObject, const SkRegion* deviceRegion, SkClipOp);
+}
+
+// A canvas without pixels that forwards the save, matrix, clip and draw calls to Rust.
+// Clips are also applied to the canvas itself, so that the clip queries of SkCanvas stay valid.
+class RustCanvas: public SkCanvas {
+public:
+    struct Param {
+        TraitObject trait;
+        ::Canvas::Drop drop;
+        ::Canvas::WillSave willSave;
+        ::Canvas::WillSaveLayer willSaveLayer;
+        ::Canvas::WillRestore willRestore;
+        ::Canvas::DidConcat didConcat;
+        ::Canvas::DidSetMatrix didSetMatrix;
+        ::Canvas::DidConcat44 didConcat44;
+        ::Canvas::DrawPaint drawPaint;
+        ::Canvas::DrawRect drawRect;
+        ::Canvas::DrawRRect drawRRect;
+        ::Canvas::DrawDRRect drawDRRect;
+        ::Canvas::DrawOval drawOval;
+        ::Canvas::DrawArc drawArc;
+        ::Canvas::DrawPath drawPath;
+        ::Canvas::DrawRegion drawRegion;
+        ::Canvas::DrawPoints drawPoints;
+        ::Canvas::DrawTextBlob drawTextBlob;
+        ::Canvas::DrawImage drawImage;
+        ::Canvas::DrawImageRect drawImageRect;
+        ::Canvas::DrawImageNine drawImageNine;
+        ::Canvas::DrawImageLattice drawImageLattice;
+        ::Canvas::DrawVertices drawVertices;
+        ::Canvas::DrawPatch drawPatch;
+        ::Canvas::DrawAtlas drawAtlas;
+        ::Canvas::DrawAnnotation drawAnnotation;
+        ::Canvas::DrawShadowRec drawShadowRec;
+        ::Canvas::DrawEdgeAAQuad drawEdgeAAQuad;
+        ::Canvas::DrawEdgeAAImageSet drawEdgeAAImageSet;
+        ::Canvas::DrawBehind drawBehind;
+        ::Canvas::ClipRect clipRect;
+        ::Canvas::ClipRRect clipRRect;
+        ::Canvas::ClipPath clipPath;
+        ::Canvas::ClipRegion clipRegion;
+    };
+
+    RustCanvas(int width, int height, const SkSurfaceProps* props, const Param& param)
+    : SkCanvas(width, height, props), _param(param) {
+    }
+
+    ~RustCanvas() override {
+        _param.drop(_param.trait);
+    }
+
+protected:
+    void willSave() override {
+        _param.willSave(_param.trait);
+    }
+
+    SaveLayerStrategy getSaveLayerStrategy(const SaveLayerRec& rec) override {
+        _param.willSaveLayer(_param.trait, rec.fBounds, rec.fPaint, rec.fSaveLayerFlags);
+        return kNoLayer_SaveLayerStrategy;
+    }
+
+    void willRestore() override {
+        _param.willRestore(_param.trait);
+    }
+
+    void didConcat(const SkMatrix& matrix) override {
+        _param.didConcat(_param.trait, &matrix);
+    }
+
+    void didSetMatrix(const SkMatrix& matrix) override {
+        _param.didSetMatrix(_param.trait, &matrix);
+    }
+
+    void didConcat44(const SkScalar colMajor[16]) override {
+        SkMatrix44 matrix(SkMatrix44::kUninitialized_Constructor);
+        matrix.setColMajorf(colMajor);
+        _param.didConcat44(_param.trait, &matrix);
+    }
+
+    void onDrawPaint(const SkPaint& paint) override {
+        _param.drawPaint(_param.trait, &paint);
+    }
+
+    void onDrawRect(const SkRect& rect, const SkPaint& paint) override {
+        _param.drawRect(_param.trait, &rect, &paint);
+    }
+
+    void onDrawRRect(const SkRRect& rrect, const SkPaint& paint) override {
+        _param.drawRRect(_param.trait, &rrect, &paint);
+    }
+
+    void onDrawDRRect(const SkRRect& outer, const SkRRect& inner, const SkPaint& paint) override {
+        _param.drawDRRect(_param.trait, &outer, &inner, &paint);
+    }
+
+    void onDrawOval(const SkRect& oval, const SkPaint& paint) override {
+        _param.drawOval(_param.trait, &oval, &paint);
+    }
+
+    void onDrawArc(const SkRect& oval, SkScalar startAngle, SkScalar sweepAngle, bool useCenter, const SkPaint& paint) override {
+        _param.drawArc(_param.trait, &oval, startAngle, sweepAngle, useCenter, &paint);
+    }
+
+    void onDrawPath(const SkPath& path, const SkPaint& paint) override {
+        _param.drawPath(_param.trait, &path, &paint);
+    }
+
+    void onDrawRegion(const SkRegion& region, const SkPaint& paint) override {
+        _param.drawRegion(_param.trait, &region, &paint);
+    }
+
+    void onDrawPoints(PointMode mode, size_t count, const SkPoint pts[], const SkPaint& paint) override {
+        _param.drawPoints(_param.trait, mode, count, pts, &paint);
+    }
+
+    void onDrawTextBlob(const SkTextBlob* blob, SkScalar x, SkScalar y, const SkPaint& paint) override {
+        _param.drawTextBlob(_param.trait, blob, x, y, &paint);
+    }
+
+    void onDrawImage(const SkImage* image, SkScalar left, SkScalar top, const SkPaint* paint) override {
+        _param.drawImage(_param.trait, image, left, top, paint);
+    }
+
+    void onDrawImageRect(const SkImage* image, const SkRect* src, const SkRect& dst, const SkPaint* paint, SrcRectConstraint constraint) override {
+        _param.drawImageRect(_param.trait, image, src, &dst, paint, constraint);
+    }
+
+    void onDrawImageNine(const SkImage* image, const SkIRect& center, const SkRect& dst, const SkPaint* paint) override {
+        _param.drawImageNine(_param.trait, image, &center, &dst, paint);
+    }
+
+    void onDrawImageLattice(const SkImage* image, const Lattice& lattice, const SkRect& dst, const SkPaint* paint) override {
+        _param.drawImageLattice(_param.trait, image, &lattice, &dst, paint);
+    }
+
+    // Bitmaps are forwarded as images.
+
+    void onDrawBitmap(const SkBitmap& bitmap, SkScalar left, SkScalar top, const SkPaint* paint) override {
+        if (auto image = SkImage::MakeFromBitmap(bitmap)) {
+            this->onDrawImage(image.get(), left, top, paint);
+        }
+    }
+
+    void onDrawBitmapRect(const SkBitmap& bitmap, const SkRect* src, const SkRect& dst, const SkPaint* paint, SrcRectConstraint constraint) override {
+        if (auto image = SkImage::MakeFromBitmap(bitmap)) {
+            this->onDrawImageRect(image.get(), src, dst, paint, constraint);
+        }
+    }
+
+    void onDrawBitmapNine(const SkBitmap& bitmap, const SkIRect& center, const SkRect& dst, const SkPaint* paint) override {
+        if (auto image = SkImage::MakeFromBitmap(bitmap)) {
+            this->onDrawImageNine(image.get(), center, dst, paint);
+        }
+    }
+
+    void onDrawBitmapLattice(const SkBitmap& bitmap, const Lattice& lattice, const SkRect& dst, const SkPaint* paint) override {
+        if (auto image = SkImage::MakeFromBitmap(bitmap)) {
+            this->onDrawImageLattice(image.get(), lattice, dst, paint);
+        }
+    }
+
+    void onDrawVerticesObject(const SkVertices* vertices, const SkVertices::Bone bones[], int boneCount, SkBlendMode mode, const SkPaint& paint) override {
+        _param.drawVertices(_param.trait, vertices, bones, boneCount, mode, &paint);
+    }
+
+    void onDrawPatch(const SkPoint cubics[12], const SkColor colors[4], const SkPoint texCoords[4], SkBlendMode mode, const SkPaint& paint) override {
+        _param.drawPatch(_param.trait, cubics, colors, texCoords, mode, &paint);
+    }
+
+    void onDrawAtlas(const SkImage* atlas, const SkRSXform xform[], const SkRect tex[], const SkColor colors[], int count, SkBlendMode mode, const SkRect* cullRect, const SkPaint* paint) override {
+        _param.drawAtlas(_param.trait, atlas, xform, tex, colors, count, mode, cullRect, paint);
+    }
+
+    void onDrawAnnotation(const SkRect& rect, const char key[], SkData* value) override {
+        _param.drawAnnotation(_param.trait, &rect, key, value);
+    }
+
+    void onDrawShadowRec(const SkPath& path, const SkDrawShadowRec& rec) override {
+        _param.drawShadowRec(_param.trait, &path, &rec.fZPlaneParams, &rec.fLightPos, rec.fLightRadius, rec.fAmbientColor, rec.fSpotColor, rec.fFlags);
+    }
+
+    void onDrawEdgeAAQuad(const SkRect& rect, const SkPoint clip[4], QuadAAFlags aaFlags, const SkColor4f& color, SkBlendMode mode) override {
+        _param.drawEdgeAAQuad(_param.trait, &rect, clip, aaFlags, &color, mode);
+    }
+
+    void onDrawEdgeAAImageSet(const ImageSetEntry imageSet[], int count, const SkPoint dstClips[], const SkMatrix preViewMatrices[], const SkPaint* paint, SrcRectConstraint constraint) override {
+        // the number of clip points and matrices is implied by the entries.
+        int dstClipCount = 0;
+        int matrixCount = 0;
+        for (int i = 0; i < count; ++i) {
+            if (imageSet[i].fHasClip) {
+                dstClipCount += 4;
+            }
+            matrixCount = std::max(matrixCount, imageSet[i].fMatrixIndex + 1);
+        }
+        _param.drawEdgeAAImageSet(_param.trait, imageSet, count, dstClips, dstClipCount, preViewMatrices, matrixCount, paint, constraint);
+    }
+
+    void onDrawBehind(const SkPaint& paint) override {
+        _param.drawBehind(_param.trait, &paint);
+    }
+
+    void onClipRect(const SkRect& rect, SkClipOp op, ClipEdgeStyle edgeStyle) override {
+        _param.clipRect(_param.trait, &rect, op, edgeStyle == kSoft_ClipEdgeStyle);
+        SkCanvas::onClipRect(rect, op, edgeStyle);
+    }
+
+    void onClipRRect(const SkRRect& rrect, SkClipOp op, ClipEdgeStyle edgeStyle) override {
+        _param.clipRRect(_param.trait, &rrect, op, edgeStyle == kSoft_ClipEdgeStyle);
+        SkCanvas::onClipRRect(rrect, op, edgeStyle);
+    }
+
+    void onClipPath(const SkPath& path, SkClipOp op, ClipEdgeStyle edgeStyle) override {
+        _param.clipPath(_param.trait, &path, op, edgeStyle == kSoft_ClipEdgeStyle);
+        SkCanvas::onClipPath(path, op, edgeStyle);
+    }
+
+    void onClipRegion(const SkRegion& deviceRegion, SkClipOp op) override {
+        _param.clipRegion(_param.trait, &deviceRegion, op);
+        SkCanvas::onClipRegion(deviceRegion, op);
+    }
+
+private:
+    Param _param;
+};
+
+extern "C" SkCanvas* C_RustCanvas_New(int width, int height, const SkSurfaceProps* props, const RustCanvas::Param* param) {
+    return new RustCanvas(width, height, props, *param);
+}
+
+//
+// core/SkRasterHandleAllocator.h
+//
+
+namespace RasterHandleAllocator {
+    extern "C" typedef void (*Drop)(TraitObject);
+    extern "C" typedef bool (*AllocHandle)(TraitObject, const SkImageInfo*, SkRasterHandleAllocator::Rec*);
+    extern "C" typedef void (*UpdateHandle)(TraitObject, SkRasterHandleAllocator::Handle, const SkMatrix*, const SkIRect*);
+}
+
+class RustRasterHandleAllocator: public SkRasterHandleAllocator {
+public:
+    struct Param {
+        TraitObject trait;
+        ::RasterHandleAllocator::Drop drop;
+        ::RasterHandleAllocator::AllocHandle allocHandle;
+        ::RasterHandleAllocator::UpdateHandle updateHandle;
+    };
+
+    explicit RustRasterHandleAllocator(const Param& param)
+    : _param(param) {
+    }
+
+    ~RustRasterHandleAllocator() override {
+        _param.drop(_param.trait);
+    }
+
+    bool allocHandle(const SkImageInfo& info, Rec* rec) override {
+        return _param.allocHandle(_param.trait, &info, rec);
+    }
+
+    void updateHandle(Handle handle, const SkMatrix& matrix, const SkIRect& clipBounds) override {
+        _param.updateHandle(_param.trait, handle, &matrix, &clipBounds);
+    }
+
+private:
+    Param _param;
+};
+
+extern "C" SkCanvas* C_RustRasterHandleAllocator_MakeCanvas(const SkImageInfo* info, const RustRasterHandleAllocator::Param* param) {
+    auto allocator = std::unique_ptr<SkRasterHandleAllocator>(new RustRasterHandleAllocator(*param));
+    return SkRasterHandleAllocator::MakeCanvas(std::move(allocator), *info).release();
+}
+
 //
 // core/SkAutoCanvasRestore.h
 //
@@ -710,6 +1457,10 @@ extern "C" void C_SkMatrix44_SkMatrix(const SkMatrix44* self, SkMatrix* m) {
     *m = SkMatrix(*self);
 }
 
+extern "C" void C_SkMatrix44_ConstructFromSkMatrix(SkMatrix44* uninitialized, const SkMatrix* m) {
+    new(uninitialized) SkMatrix44(*m);
+}
+
 extern "C" void C_SkMatrix44_Mul(const SkMatrix44* self, const SkMatrix44* rhs, SkMatrix44* result) {
     *result = *self * *rhs;
 }
@@ -818,20 +1569,111 @@ extern "C" SkShader* C_SkBitmap_makeShader(const SkBitmap* self, SkTileMode tmx,
     return self->makeShader(tmx, tmy, localMatrix).release();
 }
 
+//
+// core/SkSerialProcs.h
+//
+
+namespace SerialProcs {
+    extern "C" typedef SkData* (*SerializePicture)(void* context, SkPicture*);
+    extern "C" typedef SkData* (*SerializeImage)(void* context, SkImage*);
+    extern "C" typedef SkData* (*SerializeTypeface)(void* context, SkTypeface*);
+    extern "C" typedef SkPicture* (*DeserializePicture)(void* context, const void* data, size_t length);
+    extern "C" typedef SkImage* (*DeserializeImage)(void* context, const void* data, size_t length);
+    extern "C" typedef SkTypeface* (*DeserializeTypeface)(void* context, const void* data, size_t length);
+}
+
+// Serial procs that are implemented in Rust, procs that are not set are nullptr.
+struct RustSerialProcs {
+    void* context;
+    ::SerialProcs::SerializePicture picture;
+    ::SerialProcs::SerializeImage image;
+    ::SerialProcs::SerializeTypeface typeface;
+
+    SkSerialProcs procs() const {
+        SkSerialProcs procs;
+        auto rustProcs = const_cast<RustSerialProcs*>(this);
+        if (picture) {
+            procs.fPictureProc = [](SkPicture* picture, void* ctx) {
+                auto self = static_cast<const RustSerialProcs*>(ctx);
+                return sp(self->picture(self->context, picture));
+            };
+            procs.fPictureCtx = rustProcs;
+        }
+        if (image) {
+            procs.fImageProc = [](SkImage* image, void* ctx) {
+                auto self = static_cast<const RustSerialProcs*>(ctx);
+                return sp(self->image(self->context, image));
+            };
+            procs.fImageCtx = rustProcs;
+        }
+        if (typeface) {
+            procs.fTypefaceProc = [](SkTypeface* typeface, void* ctx) {
+                auto self = static_cast<const RustSerialProcs*>(ctx);
+                return sp(self->typeface(self->context, typeface));
+            };
+            procs.fTypefaceCtx = rustProcs;
+        }
+        return procs;
+    }
+};
+
+// Deserial procs that are implemented in Rust, procs that are not set are nullptr.
+struct RustDeserialProcs {
+    void* context;
+    ::SerialProcs::DeserializePicture picture;
+    ::SerialProcs::DeserializeImage image;
+    ::SerialProcs::DeserializeTypeface typeface;
+
+    SkDeserialProcs procs() const {
+        SkDeserialProcs procs;
+        auto rustProcs = const_cast<RustDeserialProcs*>(this);
+        if (picture) {
+            procs.fPictureProc = [](const void* data, size_t length, void* ctx) {
+                auto self = static_cast<const RustDeserialProcs*>(ctx);
+                return sp(self->picture(self->context, data, length));
+            };
+            procs.fPictureCtx = rustProcs;
+        }
+        if (image) {
+            procs.fImageProc = [](const void* data, size_t length, void* ctx) {
+                auto self = static_cast<const RustDeserialProcs*>(ctx);
+                return sp(self->image(self->context, data, length));
+            };
+            procs.fImageCtx = rustProcs;
+        }
+        if (typeface) {
+            procs.fTypefaceProc = [](const void* data, size_t length, void* ctx) {
+                auto self = static_cast<const RustDeserialProcs*>(ctx);
+                return sp(self->typeface(self->context, data, length));
+            };
+            procs.fTypefaceCtx = rustProcs;
+        }
+        return procs;
+    }
+};
+
 //
 // core/SkPicture.h
 //
 
-extern "C" SkPicture* C_SkPicture_MakeFromData(const SkData* data) {
-    return SkPicture::MakeFromData(data).release();
+extern "C" SkPicture* C_SkPicture_MakeFromData(const SkData* data, const RustDeserialProcs* procs) {
+    auto skProcs = procs ? procs->procs() : SkDeserialProcs();
+    return SkPicture::MakeFromData(data, &skProcs).release();
 }
 
-extern "C" SkPicture* C_SkPicture_MakeFromData2(const void* data, size_t size) {
-    return SkPicture::MakeFromData(data, size).release();
+extern "C" SkPicture* C_SkPicture_MakeFromData2(const void* data, size_t size, const RustDeserialProcs* procs) {
+    auto skProcs = procs ? procs->procs() : SkDeserialProcs();
+    return SkPicture::MakeFromData(data, size, &skProcs).release();
 }
 
-extern "C" SkData* C_SkPicture_serialize(const SkPicture* self) {
-    return self->serialize().release();
+extern "C" SkPicture* C_SkPicture_MakeFromStream(SkStream* stream, const RustDeserialProcs* procs) {
+    auto skProcs = procs ? procs->procs() : SkDeserialProcs();
+    return SkPicture::MakeFromStream(stream, &skProcs).release();
+}
+
+extern "C" SkData* C_SkPicture_serialize(const SkPicture* self, const RustSerialProcs* procs) {
+    auto skProcs = procs ? procs->procs() : SkSerialProcs();
+    return self->serialize(&skProcs).release();
 }
 
 extern "C" SkPicture* C_SkPicture_MakePlaceholder(const SkRect& cull) {
@@ -842,6 +1684,30 @@ extern "C" void C_SkPicture_playback(const SkPicture* self, SkCanvas* canvas) {
     self->playback(canvas);
 }
 
+namespace Picture {
+    extern "C" typedef bool (*Abort)(void* context);
+}
+
+class RustAbortCallback: public SkPicture::AbortCallback {
+public:
+    RustAbortCallback(::Picture::Abort abort, void* context)
+    : _abort(abort), _context(context) {
+    }
+
+    bool abort() override {
+        return _abort(_context);
+    }
+
+private:
+    ::Picture::Abort _abort;
+    void* _context;
+};
+
+extern "C" void C_SkPicture_playbackWithAbort(const SkPicture* self, SkCanvas* canvas, ::Picture::Abort abort, void* context) {
+    RustAbortCallback callback(abort, context);
+    self->playback(canvas, &callback);
+}
+
 extern "C" SkRect C_SkPicture_cullRect(const SkPicture* self) {
     return self->cullRect();
 }
@@ -1076,6 +1942,93 @@ extern "C" bool C_SkYUVAIndex_AreValidIndices(const SkYUVAIndex yuvaIndices[4],
     return SkYUVAIndex::AreValidIndices(yuvaIndices, numPlanes);
 }
 
+// A color matrix that moves the channel `src` to `dst`. The alpha of the result is 1, unless
+// `dst` is the alpha channel.
+static void channelMatrix(SkColorChannel src, SkColorChannel dst, float matrix[20]) {
+    std::fill(matrix, matrix + 20, 0.0f);
+    matrix[static_cast<int>(dst) * 5 + static_cast<int>(src)] = 1.0f;
+    if (dst != SkColorChannel::kA) {
+        matrix[3 * 5 + 4] = 1.0f;
+    }
+}
+
+// Converts YUVA pixmaps to RGBA pixels on the CPU.
+//
+// m81's SkImage::MakeFromYUVAPixmaps() needs a GrContext and lazy images use the YUVA planes of
+// a generator only on the GPU. So the Y, U and V channels are drawn into the color channels
+// of a raster surface and converted with Skia's YUV to RGB color matrix.
+extern "C" bool C_SkYUVAPixmaps_readPixels(
+        SkYUVColorSpace yuvColorSpace,
+        const SkPixmap* const yuvaPixmaps[],
+        const SkYUVAIndex yuvaIndices[4],
+        SkColorSpace* imageColorSpace,
+        const SkImageInfo* dstInfo,
+        void* dstPixels,
+        size_t dstRowBytes) {
+    auto colorSpace = sp(imageColorSpace);
+    auto bounds = SkRect::Make(dstInfo->dimensions());
+
+    // Draws a channel of a plane, scaled to the image size, into `dstChannel`.
+    auto drawChannel = [&](SkCanvas* canvas, int index, SkColorChannel dstChannel, SkBlendMode mode) {
+        const SkPixmap& pixmap = *yuvaPixmaps[yuvaIndices[index].fIndex];
+        auto channel = yuvaIndices[index].fChannel;
+        // Alpha8 images are drawn as alpha only, Gray8 images have the same value in all colors.
+        if (pixmap.colorType() == kAlpha_8_SkColorType) {
+            channel = SkColorChannel::kA;
+        } else if (pixmap.colorType() == kGray_8_SkColorType) {
+            channel = SkColorChannel::kR;
+        }
+        auto plane = SkImage::MakeFromRaster(pixmap, nullptr, nullptr);
+        if (!plane) {
+            return false;
+        }
+        float matrix[20];
+        channelMatrix(channel, dstChannel, matrix);
+        SkPaint paint;
+        paint.setColorFilter(SkColorFilters::Matrix(matrix));
+        paint.setBlendMode(mode);
+        paint.setFilterQuality(kLow_SkFilterQuality);
+        canvas->drawImageRect(plane, bounds, &paint);
+        return true;
+    };
+
+    auto yuv = SkSurface::MakeRaster(dstInfo->makeColorType(kN32_SkColorType).makeAlphaType(kOpaque_SkAlphaType).makeColorSpace(nullptr));
+    if (!yuv) {
+        return false;
+    }
+    auto yuvCanvas = yuv->getCanvas();
+    yuvCanvas->clear(SK_ColorBLACK);
+    if (!drawChannel(yuvCanvas, SkYUVAIndex::kY_Index, SkColorChannel::kR, SkBlendMode::kPlus)
+        || !drawChannel(yuvCanvas, SkYUVAIndex::kU_Index, SkColorChannel::kG, SkBlendMode::kPlus)
+        || !drawChannel(yuvCanvas, SkYUVAIndex::kV_Index, SkColorChannel::kB, SkBlendMode::kPlus)) {
+        return false;
+    }
+
+    bool hasAlpha = yuvaIndices[SkYUVAIndex::kA_Index].fIndex >= 0;
+    auto rgba = SkSurface::MakeRaster(yuv->imageInfo().makeAlphaType(hasAlpha ? kPremul_SkAlphaType : kOpaque_SkAlphaType));
+    if (!rgba) {
+        return false;
+    }
+    float yuvToRgb[20];
+    SkColorMatrix_YUV2RGB(yuvColorSpace, yuvToRgb);
+    SkPaint paint;
+    paint.setColorFilter(SkColorFilters::Matrix(yuvToRgb));
+    paint.setBlendMode(SkBlendMode::kSrc);
+    rgba->getCanvas()->drawImage(yuv->makeImageSnapshot(), 0, 0, &paint);
+    if (hasAlpha && !drawChannel(rgba->getCanvas(), SkYUVAIndex::kA_Index, SkColorChannel::kA, SkBlendMode::kDstIn)) {
+        return false;
+    }
+
+    SkPixmap result;
+    if (!rgba->peekPixels(&result)) {
+        return false;
+    }
+    // the pixels are in the color space of the image, they are converted to the color
+    // space of dstInfo while reading.
+    result.setColorSpace(colorSpace);
+    return result.readPixels(*dstInfo, dstPixels, dstRowBytes);
+}
+
 //
 // core/SkYUVASizeInfo.h
 //
@@ -1092,8 +2045,9 @@ extern "C" const char* C_SkFlattenable_getTypeName(const SkFlattenable* self) {
     return self->getTypeName();
 }
 
-extern "C" SkData* C_SkFlattenable_serialize(const SkFlattenable* self) {
-    return self->serialize().release();
+extern "C" SkData* C_SkFlattenable_serialize(const SkFlattenable* self, const RustSerialProcs* procs) {
+    auto skProcs = procs ? procs->procs() : SkSerialProcs();
+    return self->serialize(&skProcs).release();
 }
 
 //
@@ -1267,6 +2221,85 @@ extern "C" SkVertices* C_SkVertices_Builder_detach(SkVertices::Builder* builder)
     return builder->detach().release();
 }
 
+//
+// core/SkBBHFactory.h
+//
+
+// SkBBoxHierarchy::insert() must be called only once, so the hierarchies that are created for
+// Rust track if they were filled.
+class FillTrackingBBoxHierarchy: public SkBBoxHierarchy {
+public:
+    explicit FillTrackingBBoxHierarchy(sk_sp<SkBBoxHierarchy> bbh)
+    : _bbh(std::move(bbh)) {
+    }
+
+    void insert(const SkRect rects[], int count) override {
+        _filled = true;
+        _bbh->insert(rects, count);
+    }
+
+    void search(const SkRect& query, std::vector<int>* results) const override {
+        _bbh->search(query, results);
+    }
+
+    size_t bytesUsed() const override {
+        return sizeof(*this) + _bbh->bytesUsed();
+    }
+
+    bool isFilled() const {
+        return _filled;
+    }
+
+    // Marks the hierarchy as filled before it is, used when a picture recorder takes it over.
+    void setFilled() {
+        _filled = true;
+    }
+
+private:
+    sk_sp<SkBBoxHierarchy> _bbh;
+    bool _filled = false;
+};
+
+extern "C" void C_SkBBoxHierarchy_insert(SkBBoxHierarchy* self, const SkRect rects[], int count) {
+    self->insert(rects, count);
+}
+
+extern "C" bool C_SkBBoxHierarchy_isFilled(const SkBBoxHierarchy* self) {
+    return static_cast<const FillTrackingBBoxHierarchy*>(self)->isFilled();
+}
+
+namespace BBoxHierarchy {
+    extern "C" typedef void (*AddResult)(void* context, int index);
+}
+
+extern "C" void C_SkBBoxHierarchy_search(const SkBBoxHierarchy* self, const SkRect* query, ::BBoxHierarchy::AddResult addResult, void* context) {
+    std::vector<int> results;
+    self->search(*query, &results);
+    for (auto index: results) {
+        addResult(context, index);
+    }
+}
+
+extern "C" size_t C_SkBBoxHierarchy_bytesUsed(const SkBBoxHierarchy* self) {
+    return self->bytesUsed();
+}
+
+extern "C" void C_SkBBHFactory_destruct(SkBBHFactory* self) {
+    self->~SkBBHFactory();
+}
+
+extern "C" SkBBoxHierarchy* C_SkBBHFactory_makeBBH(const SkBBHFactory* self) {
+    return new FillTrackingBBoxHierarchy((*self)());
+}
+
+extern "C" void C_SkRTreeFactory_Construct(SkRTreeFactory* uninitialized) {
+    new(uninitialized) SkRTreeFactory();
+}
+
+extern "C" void C_SkRTreeFactory_destruct(SkRTreeFactory* self) {
+    self->~SkRTreeFactory();
+}
+
 //
 // SkPictureRecorder
 //
@@ -1275,6 +2308,12 @@ extern "C" void C_SkPictureRecorder_destruct(SkPictureRecorder *self) {
     self->~SkPictureRecorder();
 }
 
+extern "C" SkCanvas* C_SkPictureRecorder_beginRecording(SkPictureRecorder* self, const SkRect* bounds, SkBBoxHierarchy* bbh, uint32_t recordFlags) {
+    // the recorder fills the hierarchy when the recording is finished.
+    static_cast<FillTrackingBBoxHierarchy*>(bbh)->setFilled();
+    return self->beginRecording(*bounds, sp(bbh), recordFlags);
+}
+
 extern "C" SkPicture* C_SkPictureRecorder_finishRecordingAsPicture(SkPictureRecorder* self, const SkRect* cullRect) {
     if (cullRect){
         return self->finishRecordingAsPictureWithCull(*cullRect).release();
@@ -1501,6 +2540,56 @@ extern "C" SkDrawable* C_SkDrawable_Deserialize(const void* data, size_t length)
     return SkDrawable::Deserialize(data, length).release();
 }
 
+namespace Drawable {
+    extern "C" typedef void (*Drop)(TraitObject);
+    extern "C" typedef void (*GetBounds)(TraitObject, SkRect*);
+    extern "C" typedef void (*Draw)(TraitObject, SkCanvas*);
+    extern "C" typedef SkPicture* (*NewPictureSnapshot)(TraitObject);
+}
+
+class RustDrawable: public SkDrawable {
+public:
+    struct Param {
+        TraitObject trait;
+        ::Drawable::Drop drop;
+        ::Drawable::GetBounds getBounds;
+        ::Drawable::Draw draw;
+        ::Drawable::NewPictureSnapshot newPictureSnapshot;
+    };
+
+    explicit RustDrawable(const Param& param)
+    : _param(param) {
+    }
+
+    ~RustDrawable() override {
+        _param.drop(_param.trait);
+    }
+
+protected:
+    SkRect onGetBounds() override {
+        SkRect bounds;
+        _param.getBounds(_param.trait, &bounds);
+        return bounds;
+    }
+
+    void onDraw(SkCanvas* canvas) override {
+        _param.draw(_param.trait, canvas);
+    }
+
+    // If Rust does not provide a snapshot, the default records onDraw() into a picture.
+    SkPicture* onNewPictureSnapshot() override {
+        auto picture = _param.newPictureSnapshot(_param.trait);
+        return picture ? picture : SkDrawable::onNewPictureSnapshot();
+    }
+
+private:
+    Param _param;
+};
+
+extern "C" SkDrawable* C_RustDrawable_New(const RustDrawable::Param* param) {
+    return new RustDrawable(*param);
+}
+
 //
 // SkImageFilter
 //
@@ -1565,6 +2654,58 @@ extern "C" SkImageGenerator *C_SkImageGenerator_MakeFromPicture(
             sp(cs)).release();
 }
 
+namespace ImageGenerator {
+    extern "C" typedef void (*Drop)(TraitObject);
+    extern "C" typedef SkData* (*RefEncodedData)(TraitObject);
+    extern "C" typedef bool (*GetPixels)(TraitObject, const SkImageInfo*, void* pixels, size_t rowBytes);
+    extern "C" typedef bool (*QueryYUVA8)(TraitObject, SkYUVASizeInfo*, SkYUVAIndex indices[SkYUVAIndex::kIndexCount], SkYUVColorSpace*);
+    extern "C" typedef bool (*GetYUVA8Planes)(TraitObject, const SkYUVASizeInfo*, const SkYUVAIndex indices[SkYUVAIndex::kIndexCount], void* planes[4]);
+}
+
+class RustImageGenerator: public SkImageGenerator {
+public:
+    struct Param {
+        TraitObject trait;
+        ::ImageGenerator::Drop drop;
+        ::ImageGenerator::RefEncodedData refEncodedData;
+        ::ImageGenerator::GetPixels getPixels;
+        ::ImageGenerator::QueryYUVA8 queryYUVA8;
+        ::ImageGenerator::GetYUVA8Planes getYUVA8Planes;
+    };
+
+    RustImageGenerator(const SkImageInfo& info, const Param& param)
+    : SkImageGenerator(info), _param(param) {
+    }
+
+    ~RustImageGenerator() override {
+        _param.drop(_param.trait);
+    }
+
+protected:
+    sk_sp<SkData> onRefEncodedData() override {
+        return sp(_param.refEncodedData(_param.trait));
+    }
+
+    bool onGetPixels(const SkImageInfo& info, void* pixels, size_t rowBytes, const Options&) override {
+        return _param.getPixels(_param.trait, &info, pixels, rowBytes);
+    }
+
+    bool onQueryYUVA8(SkYUVASizeInfo* sizeInfo, SkYUVAIndex indices[SkYUVAIndex::kIndexCount], SkYUVColorSpace* colorSpace) const override {
+        return _param.queryYUVA8(_param.trait, sizeInfo, indices, colorSpace);
+    }
+
+    bool onGetYUVA8Planes(const SkYUVASizeInfo& sizeInfo, const SkYUVAIndex indices[SkYUVAIndex::kIndexCount], void* planes[4]) override {
+        return _param.getYUVA8Planes(_param.trait, &sizeInfo, indices, planes);
+    }
+
+private:
+    Param _param;
+};
+
+extern "C" SkImageGenerator* C_RustImageGenerator_New(const SkImageInfo* info, const RustImageGenerator::Param* param) {
+    return new RustImageGenerator(*info, *param);
+}
+
 //
 // core/SkString.h
 //
@@ -1776,10 +2917,18 @@ extern "C" void C_SkWStream_destruct(SkWStream* self) {
     self->~SkWStream();
 }
 
+extern "C" void C_SkWStream_delete(SkWStream* self) {
+    delete self;
+}
+
 extern "C" bool C_SkWStream_write(SkWStream* self, const void* buffer, size_t size) {
     return self->write(buffer, size);
 }
 
+extern "C" void C_SkWStream_flush(SkWStream* self) {
+    self->flush();
+}
+
 //
 // SkMemoryStream: public SkStreamMemory
 //
@@ -1804,6 +2953,154 @@ extern "C" SkStreamAsset* C_SkDynamicMemoryWStream_detachAsStream(SkDynamicMemor
     return self->detachAsStream().release();
 }
 
+//
+// Rust streams
+//
+
+namespace RustStream {
+    extern "C" typedef size_t (*Read)(void* state, void* buffer, size_t size);
+    extern "C" typedef bool (*Seek)(void* state, size_t position);
+    extern "C" typedef void (*Drop)(void* state);
+}
+
+// A stream asset that forwards to a Rust `Read + Seek` implementation.
+// The Rust state is dropped when the stream is deleted.
+// The stream can not be duplicated or forked, so it must not be passed to functions
+// that keep it and open copies of it later, like SkTypeface::MakeFromStream().
+class RustStreamAsset : public SkStreamAsset {
+public:
+    RustStreamAsset(void* state, size_t length, RustStream::Read read, RustStream::Seek seek, RustStream::Drop drop)
+    : _state(state), _length(length), _read(read), _seek(seek), _drop(drop), _position(0), _atEnd(false) {
+    }
+
+    ~RustStreamAsset() override {
+        _drop(_state);
+    }
+
+    size_t read(void* buffer, size_t size) override {
+        if (!buffer) {
+            size_t target = _position + std::min(size, _length - _position);
+            if (!_seek(_state, target)) {
+                _atEnd = true;
+                return 0;
+            }
+            size_t skipped = target - _position;
+            _position = target;
+            return skipped;
+        }
+        size_t read = _read(_state, buffer, size);
+        _position += read;
+        if (read < size) {
+            _atEnd = true;
+        }
+        return read;
+    }
+
+    bool isAtEnd() const override {
+        return _atEnd || _position >= _length;
+    }
+
+    bool rewind() override {
+        return seek(0);
+    }
+
+    bool hasPosition() const override {
+        return true;
+    }
+
+    size_t getPosition() const override {
+        return _position;
+    }
+
+    bool seek(size_t position) override {
+        position = std::min(position, _length);
+        if (!_seek(_state, position)) {
+            return false;
+        }
+        _position = position;
+        _atEnd = false;
+        return true;
+    }
+
+    bool move(long offset) override {
+        if (offset < 0 && size_t(-offset) > _position) {
+            return seek(0);
+        }
+        return seek(_position + offset);
+    }
+
+    size_t getLength() const override {
+        return _length;
+    }
+
+private:
+    SkStreamAsset* onDuplicate() const override {
+        return nullptr;
+    }
+
+    SkStreamAsset* onFork() const override {
+        return nullptr;
+    }
+
+    void* _state;
+    size_t _length;
+    RustStream::Read _read;
+    RustStream::Seek _seek;
+    RustStream::Drop _drop;
+    size_t _position;
+    bool _atEnd;
+};
+
+extern "C" SkStreamAsset* C_RustStreamAsset_new(void* state, size_t length, RustStream::Read read, RustStream::Seek seek, RustStream::Drop drop) {
+    return new RustStreamAsset(state, length, read, seek, drop);
+}
+
+namespace RustWStream {
+    extern "C" typedef bool (*Write)(void* state, const void* buffer, size_t size);
+    extern "C" typedef void (*Flush)(void* state);
+    extern "C" typedef void (*Drop)(void* state);
+}
+
+// A write stream that forwards to a Rust `Write` implementation.
+// The Rust state is dropped when the stream is deleted.
+class RustWStreamImpl : public SkWStream {
+public:
+    RustWStreamImpl(void* state, RustWStream::Write write, RustWStream::Flush flush, RustWStream::Drop drop)
+    : _state(state), _write(write), _flush(flush), _drop(drop), _bytesWritten(0) {
+    }
+
+    ~RustWStreamImpl() override {
+        _drop(_state);
+    }
+
+    bool write(const void* buffer, size_t size) override {
+        if (!_write(_state, buffer, size)) {
+            return false;
+        }
+        _bytesWritten += size;
+        return true;
+    }
+
+    void flush() override {
+        _flush(_state);
+    }
+
+    size_t bytesWritten() const override {
+        return _bytesWritten;
+    }
+
+private:
+    void* _state;
+    RustWStream::Write _write;
+    RustWStream::Flush _flush;
+    RustWStream::Drop _drop;
+    size_t _bytesWritten;
+};
+
+extern "C" SkWStream* C_RustWStream_new(void* state, RustWStream::Write write, RustWStream::Flush flush, RustWStream::Drop drop) {
+    return new RustWStreamImpl(state, write, flush, drop);
+}
+
 //
 // effects/
 //
@@ -2553,6 +3850,74 @@ extern "C" SkDocument* C_SkPDF_MakeDocument(SkWStream* stream, const SkPDF::Meta
     return SkPDF::MakeDocument(stream, *metadata).release();
 }
 
+extern "C" SkPDF::StructureElementNode* C_SkPDF_StructureElementNode_NewArray(size_t count) {
+    return new SkPDF::StructureElementNode[count];
+}
+
+extern "C" void C_SkPDF_StructureElementNode_DeleteArray(SkPDF::StructureElementNode* array, size_t count) {
+    for (size_t i = 0; i != count; ++i) {
+        auto& node = array[i];
+        C_SkPDF_StructureElementNode_DeleteArray(
+                const_cast<SkPDF::StructureElementNode*>(node.fChildren), node.fChildCount);
+    }
+    delete[] array;
+}
+
+/// Sets the properties of a node, the node takes ownership of the children array.
+extern "C" void C_SkPDF_StructureElementNode_set(
+        SkPDF::StructureElementNode* self,
+        SkPDF::DocumentStructureType type, int nodeId,
+        const char* alt, size_t altLength,
+        const char* lang, size_t langLength,
+        SkPDF::StructureElementNode* children, size_t childCount) {
+    self->fType = type;
+    self->fNodeId = nodeId;
+    self->fAlt.set(alt, altLength);
+    self->fLang.set(lang, langLength);
+    self->fChildren = children;
+    self->fChildCount = childCount;
+}
+
+extern "C" void C_SkPDF_Metadata_setStructureElementTreeRoot(SkPDF::Metadata* self, const SkPDF::StructureElementNode* root) {
+    self->fStructureElementTreeRoot = root;
+}
+
+extern "C" void C_SkPDF_SetNodeId(SkCanvas* canvas, int nodeId) {
+    SkPDF::SetNodeId(canvas, nodeId);
+}
+
+//
+// src/utils/SkMultiPictureDocument.h
+//
+
+extern "C" SkDocument* C_SkMultiPictureDocument_Make(SkWStream* stream) {
+    return SkMakeMultiPictureDocument(stream).release();
+}
+
+namespace MultiPictureDocument {
+    extern "C" typedef void (*AddPage)(void* context, SkPicture* picture, const SkSize* size);
+}
+
+/// Reads all pages of a multi picture document and passes them to `addPage` in order,
+/// returns false if the stream does not contain a valid document.
+extern "C" bool C_SkMultiPictureDocument_Read(
+        SkStreamAsset* stream, const RustDeserialProcs* procs,
+        ::MultiPictureDocument::AddPage addPage, void* context) {
+    auto count = SkMultiPictureDocumentReadPageCount(stream);
+    if (count <= 0 || !stream->rewind()) {
+        return false;
+    }
+    std::vector<SkDocumentPage> pages(count);
+    auto skProcs = procs ? procs->procs() : SkDeserialProcs();
+    if (!SkMultiPictureDocumentRead(stream, pages.data(), count, &skProcs)) {
+        return false;
+    }
+    for (auto& page : pages) {
+        addPage(context, page.fPicture.release(), &page.fSize);
+    }
+    return true;
+}
+
 //
 // pathops/
 //
@@ -2598,3 +3963,57 @@ extern "C" void C_SkInterpolator_setMirror(SkInterpolator* self, bool mirror) {
 extern "C" SkCanvas* C_SkMakeNullCanvas() {
     return SkMakeNullCanvas().release();
 }
+
+//
+// utils/SkNWayCanvas.h
+//
+
+extern "C" SkCanvas* C_SkNWayCanvas_new(int width, int height) {
+    return new SkNWayCanvas(width, height);
+}
+
+extern "C" void C_SkNWayCanvas_addCanvas(SkCanvas* self, SkCanvas* canvas) {
+    static_cast<SkNWayCanvas*>(self)->addCanvas(canvas);
+}
+
+extern "C" void C_SkNWayCanvas_removeCanvas(SkCanvas* self, SkCanvas* canvas) {
+    static_cast<SkNWayCanvas*>(self)->removeCanvas(canvas);
+}
+
+extern "C" void C_SkNWayCanvas_removeAll(SkCanvas* self) {
+    static_cast<SkNWayCanvas*>(self)->removeAll();
+}
+
+//
+// utils/SkPaintFilterCanvas.h
+//
+
+namespace PaintFilterCanvas {
+    extern "C" typedef bool (*Filter)(void* context, SkPaint* paint);
+    extern "C" typedef void (*Drop)(void* context);
+}
+
+class RustPaintFilterCanvas: public SkPaintFilterCanvas {
+public:
+    RustPaintFilterCanvas(SkCanvas* canvas, PaintFilterCanvas::Filter filter, PaintFilterCanvas::Drop drop, void* context)
+    : SkPaintFilterCanvas(canvas), _filter(filter), _drop(drop), _context(context) {
+    }
+
+    ~RustPaintFilterCanvas() override {
+        _drop(_context);
+    }
+
+protected:
+    bool onFilter(SkPaint& paint) const override {
+        return _filter(_context, &paint);
+    }
+
+private:
+    PaintFilterCanvas::Filter _filter;
+    PaintFilterCanvas::Drop _drop;
+    void* _context;
+};
+
+extern "C" SkCanvas* C_RustPaintFilterCanvas_new(SkCanvas* canvas, PaintFilterCanvas::Filter filter, PaintFilterCanvas::Drop drop, void* context) {
+    return new RustPaintFilterCanvas(canvas, filter, drop, context);
+}
@@ -15,6 +15,14 @@ inline sk_sp<T> sp(T* pt) {
     return sk_sp<T>(pt);
 }
 
+// A Rust trait object (`&mut dyn Trait` / `Box<dyn Trait>`), used by classes that forward
+// virtual functions to Rust.
+
+extern "C" struct TraitObject {
+    void* data;
+    void* vtable;
+};
+
 // Used in textlayout::Paragraph::findTypefaces()
 
 struct SkStrings {
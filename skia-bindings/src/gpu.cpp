@@ -325,6 +325,27 @@ extern "C" SkImage* C_SkImage_MakeFromYUVATexturesCopy(
             imageSize, imageOrigin, sp(colorSpace)).release();
 }
 
+extern "C" SkImage* C_SkImage_MakeFromYUVAPixmaps(
+        GrContext* context,
+        SkYUVColorSpace yuvColorSpace,
+        const SkPixmap* const yuvaPixmaps[],
+        size_t yuvaPixmapCount,
+        const SkYUVAIndex yuvaIndices[4],
+        SkISize imageSize,
+        GrSurfaceOrigin imageOrigin,
+        bool buildMips,
+        bool limitToMaxTextureSize,
+        SkColorSpace* imageColorSpace) {
+    SkPixmap pixmaps[SkYUVASizeInfo::kMaxCount];
+    for (size_t i = 0; i < yuvaPixmapCount && i < SkYUVASizeInfo::kMaxCount; ++i) {
+        pixmaps[i] = *yuvaPixmaps[i];
+    }
+    return SkImage::MakeFromYUVAPixmaps(
+            context,
+            yuvColorSpace, pixmaps, yuvaIndices,
+            imageSize, imageOrigin, buildMips, limitToMaxTextureSize, sp(imageColorSpace)).release();
+}
+
 extern "C" SkImage* C_SkImage_MakeFromYUVATexturesCopyWithExternalBackend(
         GrContext* context,
         SkYUVColorSpace yuvColorSpace,
@@ -1,5 +1,6 @@
 #include "modules/skshaper/include/SkShaper.h"
 #include "include/core/SkFontMgr.h"
+#include "bindings.h"
 
 extern "C" SkShaper* C_SkShaper_MakePrimitive() {
     return SkShaper::MakePrimitive().release();
@@ -105,11 +106,6 @@ extern "C" void C_SkShaper_RunHandler_delete(SkShaper::RunHandler* self) {
     delete self;
 }
 
-extern "C" struct TraitObject {
-    void* data;
-    void* vtable;
-};
-
 namespace RunHandler {
     extern "C" typedef void (*BeginLine)(TraitObject);
     extern "C" typedef void (*RunInfo)(TraitObject, const SkShaper::RunHandler::RunInfo*);
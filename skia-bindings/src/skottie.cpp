@@ -0,0 +1,247 @@
+/// Skia skottie Module C Wrapper Functions
+
+#include "bindings.h"
+
+#include "include/core/SkCanvas.h"
+#include "include/core/SkData.h"
+#include "include/core/SkFontMgr.h"
+#include "include/core/SkImage.h"
+#include "include/core/SkStream.h"
+#include "include/core/SkTypeface.h"
+#include "modules/skottie/include/Skottie.h"
+#include "modules/skottie/include/SkottieProperty.h"
+
+//
+// SkottieProperty.h
+//
+
+// The property handles are templates bindgen can not represent, so they are wrapped into
+// plain structs that are passed to Rust.
+
+struct SkottieColorPropertyHandle {
+    std::unique_ptr<skottie::ColorPropertyHandle> handle;
+};
+
+struct SkottieOpacityPropertyHandle {
+    std::unique_ptr<skottie::OpacityPropertyHandle> handle;
+};
+
+struct SkottieTextPropertyHandle {
+    std::unique_ptr<skottie::TextPropertyHandle> handle;
+};
+
+extern "C" {
+    void C_SkottieColorPropertyHandle_delete(SkottieColorPropertyHandle* self) {
+        delete self;
+    }
+
+    SkColor C_SkottieColorPropertyHandle_get(const SkottieColorPropertyHandle* self) {
+        return self->handle->get();
+    }
+
+    void C_SkottieColorPropertyHandle_set(SkottieColorPropertyHandle* self, SkColor color) {
+        self->handle->set(color);
+    }
+
+    void C_SkottieOpacityPropertyHandle_delete(SkottieOpacityPropertyHandle* self) {
+        delete self;
+    }
+
+    float C_SkottieOpacityPropertyHandle_get(const SkottieOpacityPropertyHandle* self) {
+        return self->handle->get();
+    }
+
+    void C_SkottieOpacityPropertyHandle_set(SkottieOpacityPropertyHandle* self, float opacity) {
+        self->handle->set(opacity);
+    }
+
+    void C_SkottieTextPropertyHandle_delete(SkottieTextPropertyHandle* self) {
+        delete self;
+    }
+
+    void C_SkottieTextPropertyHandle_getText(const SkottieTextPropertyHandle* self, SkString* text) {
+        *text = self->handle->get().fText;
+    }
+
+    void C_SkottieTextPropertyHandle_setText(SkottieTextPropertyHandle* self, const char* text, size_t length) {
+        auto value = self->handle->get();
+        value.fText.set(text, length);
+        self->handle->set(value);
+    }
+
+    float C_SkottieTextPropertyHandle_getTextSize(const SkottieTextPropertyHandle* self) {
+        return self->handle->get().fTextSize;
+    }
+
+    void C_SkottieTextPropertyHandle_setTextSize(SkottieTextPropertyHandle* self, float textSize) {
+        auto value = self->handle->get();
+        value.fTextSize = textSize;
+        self->handle->set(value);
+    }
+
+    SkTypeface* C_SkottieTextPropertyHandle_getTypeface(const SkottieTextPropertyHandle* self) {
+        return self->handle->get().fTypeface.release();
+    }
+
+    void C_SkottieTextPropertyHandle_setTypeface(SkottieTextPropertyHandle* self, SkTypeface* typeface) {
+        auto value = self->handle->get();
+        value.fTypeface = sp(typeface);
+        self->handle->set(value);
+    }
+}
+
+namespace PropertyObserver {
+    extern "C" typedef void (*OnColorProperty)(TraitObject, const char* nodeName, SkottieColorPropertyHandle*);
+    extern "C" typedef void (*OnOpacityProperty)(TraitObject, const char* nodeName, SkottieOpacityPropertyHandle*);
+    extern "C" typedef void (*OnTextProperty)(TraitObject, const char* nodeName, SkottieTextPropertyHandle*);
+}
+
+// Forwards the properties of an animation to Rust while it is built, the handles are owned by
+// Rust.
+class RustPropertyObserver: public skottie::PropertyObserver {
+public:
+    struct Param {
+        TraitObject trait;
+        ::PropertyObserver::OnColorProperty onColorProperty;
+        ::PropertyObserver::OnOpacityProperty onOpacityProperty;
+        ::PropertyObserver::OnTextProperty onTextProperty;
+    };
+
+    explicit RustPropertyObserver(const Param& param)
+    : _param(param) {
+    }
+
+    void onColorProperty(const char nodeName[], const LazyHandle<skottie::ColorPropertyHandle>& lh) override {
+        _param.onColorProperty(_param.trait, nodeName, new SkottieColorPropertyHandle { lh() });
+    }
+
+    void onOpacityProperty(const char nodeName[], const LazyHandle<skottie::OpacityPropertyHandle>& lh) override {
+        _param.onOpacityProperty(_param.trait, nodeName, new SkottieOpacityPropertyHandle { lh() });
+    }
+
+    void onTextProperty(const char nodeName[], const LazyHandle<skottie::TextPropertyHandle>& lh) override {
+        _param.onTextProperty(_param.trait, nodeName, new SkottieTextPropertyHandle { lh() });
+    }
+
+private:
+    Param _param;
+};
+
+//
+// Skottie.h
+//
+
+// An image asset of a single image.
+class SingleFrameImageAsset: public skottie::ImageAsset {
+public:
+    explicit SingleFrameImageAsset(sk_sp<SkImage> image)
+    : _image(std::move(image)) {
+    }
+
+    bool isMultiFrame() override {
+        return false;
+    }
+
+    sk_sp<SkImage> getFrame(float) override {
+        return _image;
+    }
+
+private:
+    sk_sp<SkImage> _image;
+};
+
+namespace ResourceProvider {
+    extern "C" typedef SkData* (*Load)(TraitObject, const char* path, const char* name);
+    extern "C" typedef SkImage* (*LoadImageAsset)(TraitObject, const char* path, const char* name, const char* id);
+    extern "C" typedef SkData* (*LoadFont)(TraitObject, const char* name, const char* url);
+}
+
+class RustResourceProvider: public skottie::ResourceProvider {
+public:
+    struct Param {
+        TraitObject trait;
+        ::ResourceProvider::Load load;
+        ::ResourceProvider::LoadImageAsset loadImageAsset;
+        ::ResourceProvider::LoadFont loadFont;
+    };
+
+    explicit RustResourceProvider(const Param& param)
+    : _param(param) {
+    }
+
+    sk_sp<SkData> load(const char path[], const char name[]) const override {
+        return sp(_param.load(_param.trait, path, name));
+    }
+
+    sk_sp<skottie::ImageAsset> loadImageAsset(const char path[], const char name[], const char id[]) const override {
+        auto image = sp(_param.loadImageAsset(_param.trait, path, name, id));
+        return image ? sk_make_sp<SingleFrameImageAsset>(std::move(image)) : nullptr;
+    }
+
+    sk_sp<SkData> loadFont(const char name[], const char url[]) const override {
+        return sp(_param.loadFont(_param.trait, name, url));
+    }
+
+private:
+    Param _param;
+};
+
+extern "C" {
+    skottie::Animation* C_skottie_Animation_MakeFromStream(
+            SkStream* stream,
+            SkFontMgr* fontMgr,
+            const RustResourceProvider::Param* resourceProvider,
+            const RustPropertyObserver::Param* propertyObserver) {
+        skottie::Animation::Builder builder;
+        if (fontMgr) {
+            builder.setFontManager(sp(fontMgr));
+        }
+        if (resourceProvider) {
+            builder.setResourceProvider(sk_make_sp<RustResourceProvider>(*resourceProvider));
+        }
+        if (propertyObserver) {
+            builder.setPropertyObserver(sk_make_sp<RustPropertyObserver>(*propertyObserver));
+        }
+        return builder.make(stream).release();
+    }
+
+    void C_skottie_Animation_ref(const skottie::Animation* self) {
+        self->ref();
+    }
+
+    void C_skottie_Animation_unref(const skottie::Animation* self) {
+        self->unref();
+    }
+
+    bool C_skottie_Animation_unique(const skottie::Animation* self) {
+        return self->unique();
+    }
+
+    void C_skottie_Animation_version(const skottie::Animation* self, SkString* version) {
+        *version = self->version();
+    }
+
+    double C_skottie_Animation_duration(const skottie::Animation* self) {
+        return self->duration();
+    }
+
+    double C_skottie_Animation_fps(const skottie::Animation* self) {
+        return self->fps();
+    }
+
+    void C_skottie_Animation_size(const skottie::Animation* self, SkSize* size) {
+        *size = self->size();
+    }
+
+    void C_skottie_Animation_seek(skottie::Animation* self, SkScalar t) {
+        self->seek(t);
+    }
+
+    void C_skottie_Animation_seekFrameTime(skottie::Animation* self, double t) {
+        self->seekFrameTime(t);
+    }
+
+    void C_skottie_Animation_render(const skottie::Animation* self, SkCanvas* canvas, const SkRect* dst) {
+        self->render(canvas, dst);
+    }
+}
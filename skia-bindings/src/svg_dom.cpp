@@ -0,0 +1,21 @@
+#include "experimental/svg/model/SkSVGDOM.h"
+
+//
+// experimental/svg/model/SkSVGDOM.h
+//
+
+extern "C" SkSVGDOM* C_SkSVGDOM_MakeFromStream(SkStream* stream) {
+    return SkSVGDOM::MakeFromStream(*stream).release();
+}
+
+extern "C" void C_SkSVGDOM_containerSize(const SkSVGDOM* self, SkSize* size) {
+    *size = self->containerSize();
+}
+
+extern "C" void C_SkSVGDOM_setContainerSize(SkSVGDOM* self, const SkSize* size) {
+    self->setContainerSize(*size);
+}
+
+extern "C" void C_SkSVGDOM_render(const SkSVGDOM* self, SkCanvas* canvas) {
+    self->render(canvas);
+}
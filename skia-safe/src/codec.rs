@@ -1,4 +1,7 @@
-// TODO: wrap SkAndroidCodec.h, SkCodecAnimation.h
+pub mod android_codec;
+pub use android_codec::AndroidCodec;
+
+pub mod codec_animation;
 
 mod decoder;
 pub use decoder::*;
@@ -0,0 +1,192 @@
+// TODO: wrap getICCProfile(), codec()
+
+use super::decoder::is_valid_buffer;
+use crate::interop::RustStream;
+use crate::prelude::*;
+use crate::{
+    AlphaType, Codec, CodecResult, ColorSpace, ColorType, Data, EncodedImageFormat, IRect, ISize,
+    ImageInfo, ZeroInitialized,
+};
+use skia_bindings as sb;
+use skia_bindings::SkAndroidCodec;
+use std::{io, ptr};
+
+/// Options for [`AndroidCodec::get_android_pixels()`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AndroidOptions {
+    /// Whether the destination memory is already zero initialized.
+    pub zero_initialized: ZeroInitialized,
+    /// If set, only this subset of the image is decoded.
+    ///
+    /// Must be a subset supported by the codec, see [`AndroidCodec::supported_subset()`].
+    pub subset: Option<IRect>,
+    /// The factor by which the image is downscaled, `1` decodes the image at full size.
+    pub sample_size: usize,
+}
+
+impl Default for AndroidOptions {
+    fn default() -> Self {
+        AndroidOptions {
+            zero_initialized: ZeroInitialized::No,
+            subset: None,
+            sample_size: 1,
+        }
+    }
+}
+
+/// A codec that supports decoding at a reduced sample size and that computes suitable output
+/// color types and color spaces.
+pub type AndroidCodec = RefHandle<SkAndroidCodec>;
+
+impl NativeDrop for SkAndroidCodec {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkAndroidCodec_delete(self) }
+    }
+}
+
+impl RefHandle<SkAndroidCodec> {
+    pub fn from_data(data: Data) -> Option<AndroidCodec> {
+        AndroidCodec::from_ptr(unsafe { sb::C_SkAndroidCodec_MakeFromData(data.into_ptr()) })
+    }
+
+    /// Creates a codec that reads the encoded image from `stream` while decoding.
+    ///
+    /// Fails with the I/O error of the stream, or with [`io::ErrorKind::InvalidData`] if the
+    /// image format is not recognized.
+    pub fn from_stream(stream: impl io::Read + io::Seek + 'static) -> io::Result<AndroidCodec> {
+        let (stream, error) = RustStream::new(stream)?.into_native();
+        let codec =
+            AndroidCodec::from_ptr(unsafe { sb::C_SkAndroidCodec_MakeFromStream(stream as _) });
+        let codec = error.into_result(codec)?;
+        codec.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "failed to create codec"))
+    }
+
+    pub fn from_codec(codec: Codec) -> Option<AndroidCodec> {
+        AndroidCodec::from_ptr(unsafe { sb::C_SkAndroidCodec_MakeFromCodec(codec.into_ptr()) })
+    }
+
+    pub fn info(&self) -> ImageInfo {
+        let mut info = ImageInfo::default();
+        unsafe { sb::C_SkAndroidCodec_getInfo(self.native(), info.native_mut()) };
+        info
+    }
+
+    pub fn encoded_format(&self) -> EncodedImageFormat {
+        EncodedImageFormat::from_native(unsafe {
+            sb::C_SkAndroidCodec_getEncodedFormat(self.native())
+        })
+    }
+
+    /// Returns the color type that is best suited for decoding, given a requested color type.
+    pub fn compute_output_color_type(&mut self, requested: ColorType) -> ColorType {
+        ColorType::from_native(unsafe {
+            self.native_mut()
+                .computeOutputColorType(requested.into_native())
+        })
+    }
+
+    pub fn compute_output_alpha_type(&mut self, requested_unpremul: bool) -> AlphaType {
+        unsafe { self.native_mut().computeOutputAlphaType(requested_unpremul) }
+    }
+
+    /// Returns the color space that is best suited for decoding to `output_color_type`.
+    pub fn compute_output_color_space(
+        &mut self,
+        output_color_type: ColorType,
+        preferred: impl Into<Option<ColorSpace>>,
+    ) -> Option<ColorSpace> {
+        ColorSpace::from_ptr(unsafe {
+            sb::C_SkAndroidCodec_computeOutputColorSpace(
+                self.native_mut(),
+                output_color_type.into_native(),
+                preferred.into().into_ptr_or_null(),
+            )
+        })
+    }
+
+    /// Returns the sample size that most closely yields an image of the requested `size`, and
+    /// the dimensions of the image when decoded with that sample size.
+    pub fn compute_sample_size(&self, size: impl Into<ISize>) -> (usize, ISize) {
+        let mut size = size.into();
+        let sample_size = unsafe { self.native().computeSampleSize(size.native_mut()) };
+        // Skia returns a sample size of at least 1.
+        (sample_size.max(1) as usize, size)
+    }
+
+    /// Returns the dimensions of the image when decoded with `sample_size`, or empty dimensions
+    /// if `sample_size` is `0` or exceeds `i32::MAX`.
+    pub fn get_sampled_dimensions(&self, sample_size: usize) -> ISize {
+        let sample_size = match sample_size.try_into() {
+            Ok(sample_size) => sample_size,
+            Err(_) => return ISize::new_empty(),
+        };
+        ISize::from_native(unsafe {
+            sb::C_SkAndroidCodec_getSampledDimensions(self.native(), sample_size)
+        })
+    }
+
+    /// Returns a subset that the codec is able to decode and that is close to `desired_subset`,
+    /// or `None` if the codec does not support subset decoding.
+    pub fn supported_subset(&self, desired_subset: impl AsRef<IRect>) -> Option<IRect> {
+        let mut subset = *desired_subset.as_ref();
+        unsafe { self.native().getSupportedSubset(subset.native_mut()) }.if_true_some(subset)
+    }
+
+    /// Returns the dimensions of `subset` when decoded with `sample_size`, or `None` if
+    /// `subset` is not supported by the codec.
+    pub fn get_sampled_subset_dimensions(
+        &self,
+        sample_size: usize,
+        subset: impl AsRef<IRect>,
+    ) -> Option<ISize> {
+        let sample_size = sample_size.try_into().ok()?;
+        let size = ISize::from_native(unsafe {
+            sb::C_SkAndroidCodec_getSampledSubsetDimensions(
+                self.native(),
+                sample_size,
+                subset.as_ref().native(),
+            )
+        });
+        (!size.is_empty()).if_true_some(size)
+    }
+
+    /// Decodes the image into `pixels`.
+    ///
+    /// `info` must match the sampled (and subsetted) dimensions of the image, see
+    /// [`get_sampled_dimensions()`](Self::get_sampled_dimensions) and
+    /// [`get_sampled_subset_dimensions()`](Self::get_sampled_subset_dimensions).
+    ///
+    /// Returns [`CodecResult::InvalidParameters`] if `pixels` is too small to hold an image
+    /// described by `info` and `row_bytes`.
+    pub fn get_android_pixels(
+        &mut self,
+        info: &ImageInfo,
+        pixels: &mut [u8],
+        row_bytes: usize,
+        options: Option<&AndroidOptions>,
+    ) -> CodecResult {
+        if !is_valid_buffer(info, pixels, row_bytes) {
+            return CodecResult::InvalidParameters;
+        }
+        let options = options.copied().unwrap_or_default();
+        let sample_size = match options.sample_size.try_into() {
+            Ok(sample_size) => sample_size,
+            Err(_) => return CodecResult::InvalidParameters,
+        };
+        let mut subset = options.subset;
+        CodecResult::from_native(unsafe {
+            sb::C_SkAndroidCodec_getAndroidPixels(
+                self.native_mut(),
+                info.native(),
+                pixels.as_mut_ptr() as _,
+                row_bytes,
+                options.zero_initialized,
+                subset
+                    .as_mut()
+                    .map(|s| s.native_mut() as *mut _)
+                    .unwrap_or(ptr::null_mut()),
+                sample_size,
+            )
+        })
+    }
+}
@@ -0,0 +1,5 @@
+pub use skia_bindings::SkCodecAnimation_DisposalMethod as DisposalMethod;
+#[test]
+fn test_disposal_method_naming() {
+    let _ = DisposalMethod::RestoreBGColor;
+}
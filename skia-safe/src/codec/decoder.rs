@@ -1,11 +1,16 @@
-// TODO: make the SkCodec wrapper complete
+// TODO: wrap getScaledDimensions(), queryYUV8(), getYUV8Planes()
 
+use crate::codec_animation::DisposalMethod;
+use crate::interop::RustStream;
 use crate::prelude::*;
-use crate::{Data, EncodedImageFormat, EncodedOrigin, IRect, ISize, ImageInfo};
-use bitflags::_core::ptr::null;
+use crate::{
+    AlphaType, Bitmap, Data, EncodedImageFormat, EncodedOrigin, IRect, ISize, Image, ImageInfo,
+};
 use skia_bindings as sb;
-use skia_bindings::{SkCodec, SkCodec_Result, SkRefCntBase};
-use std::ffi;
+use skia_bindings::{SkCodec, SkCodec_FrameInfo, SkCodec_Options, SkCodec_Result};
+use std::marker::PhantomData;
+use std::time::Duration;
+use std::{ffi, io, ptr};
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -29,19 +34,155 @@ fn test_codec_result_layout() {
     CodecResult::test_layout();
 }
 
-pub type Codec = RCHandle<SkCodec>;
+pub use skia_bindings::SkCodec_ZeroInitialized as ZeroInitialized;
+#[test]
+fn test_zero_initialized_naming() {
+    let _ = ZeroInitialized::Yes;
+}
+
+pub use skia_bindings::SkCodec_SkScanlineOrder as ScanlineOrder;
+#[test]
+fn test_scanline_order_naming() {
+    let _ = ScanlineOrder::TopDown;
+}
+
+/// Additional options to pass to [`Codec::get_pixels_with_options()`],
+/// [`Codec::start_scanline_decode()`], and [`Codec::start_incremental_decode()`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Options {
+    /// Signals that the destination memory is already zero initialized.
+    pub zero_initialized: ZeroInitialized,
+    /// The subset of the image to decode, or `None` to decode the full image.
+    ///
+    /// Must be contained in the bounds of the codec and is not supported by all codecs.
+    pub subset: Option<IRect>,
+    /// The frame to decode. Only meaningful for codecs that support multiple frames.
+    pub frame_index: usize,
+    /// The frame that is already decoded into the destination, if any.
+    ///
+    /// If set, the codec may use it as a starting point for decoding `frame_index`.
+    pub prior_frame: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            zero_initialized: ZeroInitialized::No,
+            subset: None,
+            frame_index: 0,
+            prior_frame: None,
+        }
+    }
+}
+
+/// Used in [`FrameInfo::required_frame`] to mark a frame that does not depend on another frame.
+pub const NO_FRAME: i32 = -1;
+
+/// Returned by [`Codec::repetition_count()`] if the animation repeats forever.
+pub const REPETITION_COUNT_INFINITE: i32 = -1;
+
+impl Options {
+    /// Returns the native options. The `fSubset` pointer refers to `self`.
+    ///
+    /// Returns [`CodecResult::InvalidParameters`] if a frame index exceeds `i32::MAX`.
+    pub(crate) fn to_native(&self) -> Result<SkCodec_Options, CodecResult> {
+        let frame_index = self
+            .frame_index
+            .try_into()
+            .map_err(|_| CodecResult::InvalidParameters)?;
+        let prior_frame = match self.prior_frame {
+            Some(prior_frame) => prior_frame
+                .try_into()
+                .map_err(|_| CodecResult::InvalidParameters)?,
+            None => NO_FRAME,
+        };
+        Ok(SkCodec_Options {
+            fZeroInitialized: self.zero_initialized,
+            fSubset: self.subset.native().as_ptr_or_null(),
+            fFrameIndex: frame_index,
+            fPriorFrame: prior_frame,
+        })
+    }
+}
+
+#[test]
+fn options_with_out_of_range_frame_indices_are_invalid() {
+    let options = Options {
+        frame_index: usize::MAX,
+        ..Options::default()
+    };
+    assert_eq!(
+        options.to_native().err(),
+        Some(CodecResult::InvalidParameters)
+    );
+    let options = Options {
+        prior_frame: Some(usize::MAX),
+        ..Options::default()
+    };
+    assert_eq!(
+        options.to_native().err(),
+        Some(CodecResult::InvalidParameters)
+    );
+}
+
+/// Information about a single frame of an animated image.
+///
+/// Note that the Skia version these bindings are built with does not report a frame's blend mode;
+/// frames that depend on a prior frame are always composited on top of it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(C)]
+pub struct FrameInfo {
+    /// The earliest frame this frame needs to be blended with, or [`NO_FRAME`] if this frame is
+    /// independent.
+    pub required_frame: i32,
+    /// The number of milliseconds to show this frame.
+    pub duration: i32,
+    /// Whether the end marker for this frame is contained in the encoded data.
+    pub fully_received: bool,
+    pub alpha_type: AlphaType,
+    /// How this frame should be modified before decoding the next one.
+    pub disposal_method: DisposalMethod,
+}
+
+impl NativeTransmutable<SkCodec_FrameInfo> for FrameInfo {}
+
+#[test]
+fn test_frame_info_layout() {
+    FrameInfo::test_layout();
+}
 
-impl NativeBase<SkRefCntBase> for SkCodec {}
+pub type Codec = RefHandle<SkCodec>;
 
-impl NativeRefCountedBase for SkCodec {
-    type Base = SkRefCntBase;
+impl NativeDrop for SkCodec {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkCodec_delete(self) }
+    }
 }
 
-impl RCHandle<SkCodec> {
+impl RefHandle<SkCodec> {
     pub fn from_data(data: Data) -> Option<Codec> {
         Codec::from_ptr(unsafe { sb::C_SkCodec_MakeFromData(data.into_ptr()) })
     }
 
+    /// Creates a codec that reads the encoded image from `stream` while decoding.
+    ///
+    /// Fails with the I/O error of the stream, or with [`io::ErrorKind::InvalidData`] if the
+    /// image format is not recognized.
+    pub fn from_stream(stream: impl io::Read + io::Seek + 'static) -> io::Result<Codec> {
+        let (stream, error) = RustStream::new(stream)?.into_native();
+        let mut result = CodecResult::Unimplemented;
+        let codec = Codec::from_ptr(unsafe {
+            sb::C_SkCodec_MakeFromStream(stream as _, result.native_mut())
+        });
+        let codec = error.into_result(codec)?;
+        codec.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("failed to create codec: {:?}", result),
+            )
+        })
+    }
+
     pub fn info(&self) -> ImageInfo {
         let mut info = ImageInfo::default();
         unsafe { sb::C_SkCodec_getInfo(self.native(), info.native_mut()) };
@@ -64,6 +205,14 @@ impl RCHandle<SkCodec> {
         unsafe { sb::C_SkCodec_getEncodedFormat(self.native()) }
     }
 
+    /// Returns a subset that the codec is able to decode and that is close to `desired_subset`,
+    /// or `None` if the codec does not support subset decoding.
+    pub fn valid_subset(&self, desired_subset: impl AsRef<IRect>) -> Option<IRect> {
+        let mut subset = *desired_subset.as_ref();
+        unsafe { sb::C_SkCodec_getValidSubset(self.native(), subset.native_mut()) }
+            .if_true_some(subset)
+    }
+
     pub unsafe fn get_pixels(
         &mut self,
         info: &ImageInfo,
@@ -74,7 +223,361 @@ impl RCHandle<SkCodec> {
             info.native(),
             pixels,
             row_bytes,
-            null(),
+            ptr::null(),
         ))
     }
+
+    /// Decodes the image into `pixels`.
+    ///
+    /// Returns [`CodecResult::InvalidParameters`] if `pixels` is too small to hold an image
+    /// described by `info` and `row_bytes`.
+    pub fn get_pixels_with_options(
+        &mut self,
+        info: &ImageInfo,
+        pixels: &mut [u8],
+        row_bytes: usize,
+        options: Option<&Options>,
+    ) -> CodecResult {
+        if !is_valid_buffer(info, pixels, row_bytes) {
+            return CodecResult::InvalidParameters;
+        }
+        let options = match options.map(Options::to_native).transpose() {
+            Ok(options) => options,
+            Err(error) => return error,
+        };
+        CodecResult::from_native(unsafe {
+            self.native_mut().getPixels(
+                info.native(),
+                pixels.as_mut_ptr() as _,
+                row_bytes,
+                options.as_ptr_or_null(),
+            )
+        })
+    }
+
+    /// Decodes the image into a new raster [`Image`].
+    ///
+    /// If `info` is `None`, the image is decoded with the codec's [`info()`](Self::info).
+    /// If the input is incomplete, the partially decoded image is returned.
+    pub fn get_image(
+        &mut self,
+        info: impl Into<Option<ImageInfo>>,
+        options: Option<&Options>,
+    ) -> Result<Image, CodecResult> {
+        let info = info.into().unwrap_or_else(|| self.info());
+        let mut bitmap = Bitmap::new();
+        if !bitmap.try_alloc_pixels_info(&info, None) {
+            return Err(CodecResult::InternalError);
+        }
+        let options = options.map(Options::to_native).transpose()?;
+        let result = CodecResult::from_native(unsafe {
+            self.native_mut().getPixels(
+                info.native(),
+                bitmap.pixels(),
+                bitmap.row_bytes(),
+                options.as_ptr_or_null(),
+            )
+        });
+        match result {
+            CodecResult::Success | CodecResult::IncompleteInput | CodecResult::ErrorInInput => {
+                bitmap.set_immutable();
+                Image::from_bitmap(&bitmap).ok_or(CodecResult::InternalError)
+            }
+            error => Err(error),
+        }
+    }
+
+    /// Prepares the codec for decoding the image line by line with
+    /// [`get_scanlines()`](Self::get_scanlines) and [`skip_scanlines()`](Self::skip_scanlines).
+    pub fn start_scanline_decode(
+        &mut self,
+        dst_info: &ImageInfo,
+        options: Option<&Options>,
+    ) -> CodecResult {
+        let options = match options.map(Options::to_native).transpose() {
+            Ok(options) => options,
+            Err(error) => return error,
+        };
+        CodecResult::from_native(unsafe {
+            self.native_mut()
+                .startScanlineDecode(dst_info.native(), options.as_ptr_or_null())
+        })
+    }
+
+    /// Decodes the next `count_lines` scanlines into `dst` and returns the number of lines
+    /// successfully decoded.
+    ///
+    /// Returns `0` if `count_lines` exceeds `i32::MAX` or if `dst` can not hold `count_lines`
+    /// rows of the image info the scanline decode was started with.
+    pub fn get_scanlines(&mut self, dst: &mut [u8], count_lines: usize, row_bytes: usize) -> usize {
+        let count_lines: i32 = match count_lines.try_into() {
+            Ok(count_lines) => count_lines,
+            Err(_) => return 0,
+        };
+        let dst_info = self
+            .dst_info()
+            .with_dimensions((self.dst_info().width(), count_lines));
+        if !is_valid_buffer(&dst_info, dst, row_bytes) {
+            return 0;
+        }
+        unsafe {
+            self.native_mut()
+                .getScanlines(dst.as_mut_ptr() as _, count_lines, row_bytes)
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    /// Skips `count_lines` scanlines. Returns `false` if `count_lines` exceeds `i32::MAX` or
+    /// if the lines could not be skipped.
+    pub fn skip_scanlines(&mut self, count_lines: usize) -> bool {
+        match count_lines.try_into() {
+            Ok(count_lines) => unsafe { self.native_mut().skipScanlines(count_lines) },
+            Err(_) => false,
+        }
+    }
+
+    pub fn scanline_order(&self) -> ScanlineOrder {
+        unsafe { sb::C_SkCodec_getScanlineOrder(self.native()) }
+    }
+
+    /// Returns the y-coordinate of the next row to be returned by the scanline decoder.
+    pub fn next_scanline(&self) -> i32 {
+        unsafe { sb::C_SkCodec_nextScanline(self.native()) }
+    }
+
+    /// Returns the output y-coordinate of the row that corresponds to the `input_scanline`.
+    pub fn output_scanline(&self, input_scanline: i32) -> i32 {
+        unsafe { self.native().outputScanline(input_scanline) }
+    }
+
+    /// Prepares the codec for decoding the image into `dst` step by step, as more data becomes
+    /// available.
+    ///
+    /// The returned [`IncrementalDecoder`] borrows the codec and the destination pixels until
+    /// decoding is finished.
+    pub fn start_incremental_decode<'a>(
+        &'a mut self,
+        dst_info: &ImageInfo,
+        dst: &'a mut [u8],
+        row_bytes: usize,
+        options: Option<&Options>,
+    ) -> Result<IncrementalDecoder<'a>, CodecResult> {
+        if !is_valid_buffer(dst_info, dst, row_bytes) {
+            return Err(CodecResult::InvalidParameters);
+        }
+        let options = options.map(Options::to_native).transpose()?;
+        let result = CodecResult::from_native(unsafe {
+            self.native_mut().startIncrementalDecode(
+                dst_info.native(),
+                dst.as_mut_ptr() as _,
+                row_bytes,
+                options.as_ptr_or_null(),
+            )
+        });
+        match result {
+            CodecResult::Success => Ok(IncrementalDecoder {
+                codec: self,
+                pd: PhantomData,
+            }),
+            error => Err(error),
+        }
+    }
+
+    /// Returns the number of frames in the image, `1` for still images.
+    ///
+    /// May require parsing the complete input.
+    pub fn frame_count(&mut self) -> usize {
+        unsafe { sb::C_SkCodec_getFrameCount(self.native_mut()) }
+            .try_into()
+            .unwrap()
+    }
+
+    /// Returns information about the frame at `index`, or `None` if the index is out of range
+    /// or the image is not animated.
+    pub fn frame_info(&self, index: usize) -> Option<FrameInfo> {
+        let index = index.try_into().ok()?;
+        let mut info = FrameInfo {
+            required_frame: NO_FRAME,
+            duration: 0,
+            fully_received: false,
+            alpha_type: AlphaType::Unknown,
+            disposal_method: DisposalMethod::Keep,
+        };
+        unsafe { sb::C_SkCodec_getFrameInfo(self.native(), index, info.native_mut()) }
+            .if_true_some(info)
+    }
+
+    /// Returns information about all frames, or an empty `Vec` for still images.
+    pub fn frame_infos(&mut self) -> Vec<FrameInfo> {
+        (0..self.frame_count())
+            .filter_map(|i| self.frame_info(i))
+            .collect()
+    }
+
+    /// Returns the number of times the animation should be repeated after the first play,
+    /// or [`REPETITION_COUNT_INFINITE`].
+    pub fn repetition_count(&mut self) -> i32 {
+        unsafe { sb::C_SkCodec_getRepetitionCount(self.native_mut()) }
+    }
+
+    /// Returns an iterator over the fully composited frames of an animated image.
+    ///
+    /// Still images yield one frame.
+    pub fn frames(&mut self) -> Frames {
+        let info = ImageInfo::new_n32_premul(self.dimensions(), self.info().color_space());
+        let frame_count = self.frame_count();
+        Frames {
+            codec: self,
+            info,
+            frame_count,
+            next: 0,
+            prior: None,
+        }
+    }
+
+    fn dst_info(&self) -> &ImageInfo {
+        ImageInfo::from_native_ref(&self.native().fDstInfo)
+    }
+}
+
+/// An incremental decode that was started with [`Codec::start_incremental_decode()`].
+pub struct IncrementalDecoder<'a> {
+    codec: &'a mut Codec,
+    pd: PhantomData<&'a mut [u8]>,
+}
+
+impl IncrementalDecoder<'_> {
+    /// Continues decoding into the destination pixels.
+    ///
+    /// Returns [`CodecResult::Success`] if the image is fully decoded. If the input is incomplete,
+    /// [`CodecResult::IncompleteInput`] is returned together with the number of rows that were
+    /// decoded so far. Calling this function again continues where decoding stopped.
+    pub fn incremental_decode(&mut self) -> (CodecResult, Option<usize>) {
+        let mut rows_decoded = 0;
+        let result = CodecResult::from_native(unsafe {
+            sb::C_SkCodec_incrementalDecode(self.codec.native_mut(), &mut rows_decoded)
+        });
+        match result {
+            CodecResult::IncompleteInput | CodecResult::ErrorInInput => {
+                (result, Some(rows_decoded.try_into().unwrap()))
+            }
+            _ => (result, None),
+        }
+    }
+}
+
+/// A fully composited frame of an animated image.
+#[derive(Clone)]
+pub struct Frame {
+    pub index: usize,
+    pub image: Image,
+    /// The time this frame should be shown.
+    pub duration: Duration,
+}
+
+/// Iterator over the frames of an animated image, created by [`Codec::frames()`].
+///
+/// Decoding errors are reported once, iteration ends afterwards.
+pub struct Frames<'a> {
+    codec: &'a mut Codec,
+    info: ImageInfo,
+    frame_count: usize,
+    next: usize,
+    // The most recent frame that can be used as the starting point for decoding a later frame.
+    prior: Option<(usize, Bitmap)>,
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Result<Frame, CodecResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.frame_count {
+            return None;
+        }
+        let index = self.next;
+        self.next += 1;
+
+        let frame_info = self.codec.frame_info(index);
+        let mut bitmap = Bitmap::new();
+        if !bitmap.try_alloc_pixels_flags(&self.info) {
+            self.next = self.frame_count;
+            return Some(Err(CodecResult::InternalError));
+        }
+
+        let mut options = Options {
+            zero_initialized: ZeroInitialized::Yes,
+            frame_index: index,
+            ..Options::default()
+        };
+
+        // If there is no usable prior frame, the codec decodes the required frames itself.
+        if let (Some(frame_info), Some((prior_index, prior))) = (frame_info, &self.prior) {
+            if frame_info.required_frame != NO_FRAME
+                && *prior_index >= frame_info.required_frame.try_into().unwrap()
+                && prior
+                    .pixmap()
+                    .read_pixels_to_pixmap(bitmap.pixmap(), (0, 0))
+            {
+                options.zero_initialized = ZeroInitialized::No;
+                options.prior_frame = Some(*prior_index);
+            }
+        }
+
+        let options = match options.to_native() {
+            Ok(options) => options,
+            Err(error) => {
+                self.next = self.frame_count;
+                return Some(Err(error));
+            }
+        };
+        let result = CodecResult::from_native(unsafe {
+            self.codec.native_mut().getPixels(
+                self.info.native(),
+                bitmap.pixels(),
+                bitmap.row_bytes(),
+                &options,
+            )
+        });
+
+        match result {
+            CodecResult::Success | CodecResult::IncompleteInput | CodecResult::ErrorInInput => {}
+            error => {
+                self.next = self.frame_count;
+                return Some(Err(error));
+            }
+        }
+
+        bitmap.set_immutable();
+        let disposal_method = frame_info
+            .map(|fi| fi.disposal_method)
+            .unwrap_or(DisposalMethod::Keep);
+        if disposal_method != DisposalMethod::RestorePrevious {
+            self.prior = Some((index, bitmap.clone()));
+        }
+
+        let duration = frame_info
+            .map(|fi| Duration::from_millis(fi.duration.max(0) as u64))
+            .unwrap_or_default();
+
+        Some(
+            Image::from_bitmap(&bitmap)
+                .map(|image| Frame {
+                    index,
+                    image,
+                    duration,
+                })
+                .ok_or(CodecResult::InternalError),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.frame_count - self.next;
+        (0, Some(remaining))
+    }
+}
+
+/// Returns `true` if `pixels` can hold an image described by `info` and `row_bytes`.
+pub(crate) fn is_valid_buffer(info: &ImageInfo, pixels: &[u8], row_bytes: usize) -> bool {
+    info.valid_row_bytes(row_bytes) && pixels.len() >= info.compute_byte_size(row_bytes)
 }
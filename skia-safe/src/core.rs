@@ -30,6 +30,9 @@ pub use canvas::SrcRectConstraint;
 pub use canvas::TopLayerPixels as CanvasTopLayerPixels;
 pub use canvas::{AutoCanvasRestore, Canvas, OwnedCanvas};
 
+mod canvas_impl;
+pub use canvas_impl::*;
+
 mod clip_op;
 pub use clip_op::*;
 
@@ -67,7 +70,7 @@ mod deferred_display_list_recorder;
 pub use deferred_display_list_recorder::*;
 
 pub mod document;
-pub use document::Document;
+pub use document::{Document, WriterDocument};
 
 pub mod draw_looper;
 #[deprecated(since = "0.12.0", note = "use draw_looper::BlurShadowRec")]
@@ -75,7 +78,7 @@ pub use draw_looper::BlurShadowRec as DrawLooperBlurShadowRec;
 pub use draw_looper::DrawLooper;
 
 pub mod drawable;
-pub use drawable::Drawable;
+pub use drawable::{Drawable, DrawableImpl};
 
 mod encoded_image_format;
 pub use encoded_image_format::*;
@@ -210,6 +213,9 @@ pub use path_types::*;
 mod picture;
 pub use picture::*;
 
+mod picture_op;
+pub use picture_op::*;
+
 pub mod picture_recorder;
 pub use picture_recorder::PictureRecorder;
 #[deprecated(since = "0.12.0", note = "use picture_recorder::RecordFlags")]
@@ -252,6 +258,9 @@ pub use rsxform::*;
 mod scalar_;
 pub use scalar_::*;
 
+mod serial_procs;
+pub use serial_procs::*;
+
 pub mod shader;
 #[deprecated(since = "0.12.0", note = "use shader::GradientInfo")]
 pub use shader::GradientInfo as ShaderGradientInfo;
@@ -332,8 +341,11 @@ pub use vertices::VertexMode as VerticesVertexMode;
 pub use vertices::Vertices;
 
 pub mod yuva_index;
+pub use yuva_index::PlaneLayout as YUVAPlaneLayout;
 pub use yuva_index::YUVAIndex;
 
+mod yuva_image_generator;
+
 mod yuva_size_info;
 pub use yuva_size_info::*;
 
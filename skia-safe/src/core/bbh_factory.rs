@@ -1,17 +1,152 @@
 use crate::prelude::*;
-use skia_bindings::{SkBBHFactory, SkBBoxHierarchy};
+use crate::Rect;
+use skia_bindings as sb;
+use skia_bindings::{SkBBHFactory, SkBBoxHierarchy, SkRTreeFactory, SkRefCntBase};
+use std::ffi::c_void;
+use std::ops::{Deref, DerefMut};
 
-// TODO: complete the wrapper
+/// A spatial index of bounding boxes.
+///
+/// A picture recorded with a bounding box hierarchy only plays back the operations that
+/// intersect the clip of the canvas.
 pub type BBoxHierarchy = RCHandle<SkBBoxHierarchy>;
 
-// TODO: complete the wrapper
+impl NativeRefCountedBase for SkBBoxHierarchy {
+    type Base = SkRefCntBase;
+}
+
+impl RCHandle<SkBBoxHierarchy> {
+    /// Creates an empty R-Tree.
+    pub fn new_rtree() -> BBoxHierarchy {
+        RTreeFactory::new().make_bbh()
+    }
+
+    /// Inserts `rects`, the index of a rect in `rects` is the value that is returned by
+    /// [`search()`](Self::search) when it intersects the query.
+    ///
+    /// # Panics
+    ///
+    /// The hierarchy can be filled only once, with all bounding boxes. Panics if it is already
+    /// filled, or was passed to a picture recorder with `begin_recording_with_bbh()`.
+    pub fn insert(&mut self, rects: &[Rect]) -> &mut Self {
+        assert!(
+            !self.is_filled(),
+            "the bounding box hierarchy is already filled"
+        );
+        unsafe {
+            sb::C_SkBBoxHierarchy_insert(
+                self.native_mut(),
+                rects.native().as_ptr(),
+                rects.len().try_into().unwrap(),
+            )
+        }
+        self
+    }
+
+    /// Returns `true` if the hierarchy was filled by [`insert()`](Self::insert) or a picture
+    /// recorder.
+    pub fn is_filled(&self) -> bool {
+        unsafe { sb::C_SkBBoxHierarchy_isFilled(self.native()) }
+    }
+
+    /// Returns the indices of the bounding boxes that intersect `query`.
+    pub fn search(&self, query: impl AsRef<Rect>) -> Vec<usize> {
+        let mut results = Vec::new();
+        unsafe {
+            sb::C_SkBBoxHierarchy_search(
+                self.native(),
+                query.as_ref().native(),
+                Some(add_result),
+                &mut results as *mut Vec<usize> as *mut c_void,
+            )
+        }
+        results
+    }
+
+    /// Returns the approximate size in bytes of the hierarchy.
+    pub fn bytes_used(&self) -> usize {
+        unsafe { sb::C_SkBBoxHierarchy_bytesUsed(self.native()) }
+    }
+}
+
+unsafe extern "C" fn add_result(results: *mut c_void, index: i32) {
+    let results = &mut *(results as *mut Vec<usize>);
+    results.push(index.try_into().unwrap())
+}
+
 pub type BBHFactory = Handle<SkBBHFactory>;
 
 impl NativeDrop for SkBBHFactory {
     fn drop(&mut self) {
-        unimplemented!()
+        unsafe { sb::C_SkBBHFactory_destruct(self) }
     }
 }
 
-// TODO: complete the implementation
-impl Handle<SkBBHFactory> {}
+impl Handle<SkBBHFactory> {
+    /// Creates a new, empty bounding box hierarchy.
+    pub fn make_bbh(&self) -> BBoxHierarchy {
+        BBoxHierarchy::from_ptr(unsafe { sb::C_SkBBHFactory_makeBBH(self.native()) }).unwrap()
+    }
+}
+
+/// A factory for R-Tree bounding box hierarchies.
+pub type RTreeFactory = Handle<SkRTreeFactory>;
+
+impl NativeDrop for SkRTreeFactory {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkRTreeFactory_destruct(self) }
+    }
+}
+
+impl Default for Handle<SkRTreeFactory> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Handle<SkRTreeFactory> {
+    type Target = BBHFactory;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { transmute_ref(self) }
+    }
+}
+
+impl DerefMut for Handle<SkRTreeFactory> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { transmute_ref_mut(self) }
+    }
+}
+
+impl Handle<SkRTreeFactory> {
+    pub fn new() -> RTreeFactory {
+        RTreeFactory::construct(|factory| unsafe { sb::C_SkRTreeFactory_Construct(factory) })
+    }
+}
+
+#[test]
+fn rtree_search_returns_intersecting_indices() {
+    let mut rtree = BBoxHierarchy::new_rtree();
+    assert!(!rtree.is_filled());
+    rtree.insert(&[
+        Rect::from_xywh(0.0, 0.0, 10.0, 10.0),
+        Rect::from_xywh(100.0, 100.0, 10.0, 10.0),
+        Rect::from_xywh(5.0, 5.0, 10.0, 10.0),
+    ]);
+    let mut results = rtree.search(Rect::from_xywh(0.0, 0.0, 6.0, 6.0));
+    results.sort();
+    assert_eq!(results, vec![0, 2]);
+    assert!(rtree
+        .search(Rect::from_xywh(50.0, 50.0, 10.0, 10.0))
+        .is_empty());
+    assert!(rtree.bytes_used() > 0);
+    assert!(rtree.is_filled());
+}
+
+#[test]
+#[should_panic]
+fn rtree_can_not_be_filled_twice() {
+    let mut rtree = BBoxHierarchy::new_rtree();
+    rtree.insert(&[Rect::from_wh(10.0, 10.0)]);
+    rtree.clone().insert(&[Rect::from_wh(10.0, 10.0)]);
+}
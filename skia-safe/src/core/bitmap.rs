@@ -1,7 +1,7 @@
 use crate::prelude::*;
 use crate::{
-    AlphaType, Color, ColorSpace, ColorType, IPoint, IRect, ISize, ImageInfo, Paint, PixelRef,
-    Pixmap,
+    AlphaType, Color, ColorSpace, ColorType, IPoint, IRect, ISize, ImageInfo, Paint, Pixel,
+    PixelRef, Pixmap,
 };
 use crate::{Matrix, Shader, TileMode};
 use skia_bindings as sb;
@@ -37,6 +37,10 @@ impl Handle<SkBitmap> {
         Pixmap::from_native_ref(&self.native().fPixmap)
     }
 
+    fn pixmap_mut(&mut self) -> &mut Pixmap {
+        Pixmap::from_native_ref_mut(&mut self.native_mut().fPixmap)
+    }
+
     pub fn info(&self) -> &ImageInfo {
         self.pixmap().info()
     }
@@ -326,8 +330,37 @@ impl Handle<SkBitmap> {
         self.native().getAddr(p.x, p.y)
     }
 
-    // TODO: get_addr_32()?
-    // TODO: get_addr_16()?
+    /// Returns the pixels as a slice of `P`, see [`Pixmap::pixels()`].
+    pub fn pixels<P: Pixel>(&self) -> Option<&[P]> {
+        self.pixmap().pixels()
+    }
+
+    /// Returns the pixels as a mutable slice of `P`, see [`Pixmap::pixels()`].
+    ///
+    /// Returns `None` if the bitmap is [immutable](Self::is_immutable), because its pixels may
+    /// be shared with images. Call [`notify_pixels_changed()`](Self::notify_pixels_changed)
+    /// after modifying pixels that may have been drawn before.
+    pub fn pixels_mut<P: Pixel>(&mut self) -> Option<&mut [P]> {
+        if self.is_immutable() {
+            return None;
+        }
+        // the pixels of a mutable bitmap are owned by its pixel ref and are writable.
+        unsafe { self.pixmap_mut().pixels_mut() }
+    }
+
+    /// Returns an iterator over the rows of the bitmap, see [`Pixmap::rows()`].
+    pub fn rows<P: Pixel>(&self) -> Option<impl Iterator<Item = &[P]>> {
+        self.pixmap().rows()
+    }
+
+    /// Returns an iterator over the mutable rows of the bitmap, see
+    /// [`pixels_mut()`](Self::pixels_mut).
+    pub fn rows_mut<P: Pixel>(&mut self) -> Option<impl Iterator<Item = &mut [P]>> {
+        if self.is_immutable() {
+            return None;
+        }
+        unsafe { self.pixmap_mut().rows_mut() }
+    }
 
     pub fn extract_subset(&self, dst: &mut Self, subset: impl AsRef<IRect>) -> bool {
         unsafe {
@@ -419,3 +452,28 @@ fn shader_with_tilemode() {
     let bm = Bitmap::new();
     let _shader = bm.to_shader((TileMode::Decal, TileMode::Mirror), None);
 }
+
+#[test]
+fn typed_pixels_of_allocated_bitmap() {
+    let mut bm = Bitmap::new();
+    bm.alloc_pixels_flags(&ImageInfo::new(
+        (4, 3),
+        ColorType::RGBA8888,
+        AlphaType::Unpremul,
+        None,
+    ));
+    bm.erase_color(Color::from_argb(0xff, 0x10, 0x20, 0x30));
+    assert!(bm.pixels::<[u8; 4]>().unwrap()[..12]
+        .iter()
+        .all(|p| *p == [0x10, 0x20, 0x30, 0xff]));
+
+    for (y, row) in bm.rows_mut::<[u8; 4]>().unwrap().enumerate() {
+        row.iter_mut().for_each(|p| p[0] = y as u8);
+    }
+    assert_eq!(bm.get_color((0, 2)), Color::from_argb(0xff, 2, 0x20, 0x30));
+    assert!(bm.pixels::<u16>().is_none());
+
+    bm.set_immutable();
+    assert!(bm.pixels_mut::<[u8; 4]>().is_none());
+    assert!(bm.pixels::<[u8; 4]>().is_some());
+}
@@ -3,13 +3,14 @@ use crate::gpu;
 use crate::prelude::*;
 use crate::{
     scalar, vertices, Bitmap, BlendMode, ClipOp, Color, Data, Font, IPoint, IRect, ISize, Image,
-    ImageFilter, ImageInfo, Matrix, Paint, Path, Picture, Point, QuickReject, RRect, Rect, Region,
-    Surface, SurfaceProps, TextBlob, TextEncoding, Vector, Vertices,
+    ImageFilter, ImageInfo, Matrix, Matrix44, Paint, Path, Picture, Point, QuickReject, RRect,
+    RSXform, Rect, Region, Surface, SurfaceProps, TextBlob, TextEncoding, Vector, Vertices,
 };
 use crate::{u8cpu, Drawable, Pixmap};
 use skia_bindings as sb;
 use skia_bindings::{
-    SkAutoCanvasRestore, SkCanvas, SkCanvas_SaveLayerFlagsSet_kF16ColorType,
+    SkAutoCanvasRestore, SkCanvas, SkCanvas_ImageSetEntry,
+    SkCanvas_SaveLayerFlagsSet_kF16ColorType,
     SkCanvas_SaveLayerFlagsSet_kInitWithPrevious_SaveLayerFlag, SkCanvas_SaveLayerRec, SkImage,
     SkImageFilter, SkMatrix, SkPaint, SkRect,
 };
@@ -17,7 +18,7 @@ use std::convert::TryInto;
 use std::ffi::CString;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
-use std::slice;
+use std::{ptr, slice};
 
 pub use lattice::Lattice;
 
@@ -120,6 +121,47 @@ fn test_src_rect_constraint_naming() {
     let _ = SrcRectConstraint::Fast;
 }
 
+bitflags! {
+    /// The edges of a quad that are anti-aliased.
+    pub struct QuadAAFlags: u32 {
+        const LEFT = sb::SkCanvas_QuadAAFlags_kLeft_QuadAAFlag as _;
+        const TOP = sb::SkCanvas_QuadAAFlags_kTop_QuadAAFlag as _;
+        const RIGHT = sb::SkCanvas_QuadAAFlags_kRight_QuadAAFlag as _;
+        const BOTTOM = sb::SkCanvas_QuadAAFlags_kBottom_QuadAAFlag as _;
+        const ALL = sb::SkCanvas_QuadAAFlags_kAll_QuadAAFlags as _;
+    }
+}
+
+/// An image of a set that is drawn with edge anti-aliasing.
+#[derive(Clone)]
+pub struct ImageSetEntry {
+    pub image: Image,
+    pub src_rect: Rect,
+    pub dst_rect: Rect,
+    /// The index of the entry's matrix in the pre-view matrices of the set, `None` if the entry
+    /// does not have a matrix.
+    pub matrix_index: Option<usize>,
+    pub alpha: f32,
+    pub aa_flags: QuadAAFlags,
+    /// `true` if the entry's 4 clip points follow the clip points of the previous entries in the
+    /// destination clips of the set.
+    pub has_clip: bool,
+}
+
+impl ImageSetEntry {
+    pub(crate) fn from_native(native: &SkCanvas_ImageSetEntry) -> ImageSetEntry {
+        ImageSetEntry {
+            image: Image::from_unshared_ptr(native.fImage.fPtr as *mut SkImage).unwrap(),
+            src_rect: Rect::from_native(native.fSrcRect),
+            dst_rect: Rect::from_native(native.fDstRect),
+            matrix_index: native.fMatrixIndex.try_into().ok(),
+            alpha: native.fAlpha,
+            aa_flags: QuadAAFlags::from_bits_truncate(native.fAAFlags),
+            has_clip: native.fHasClip,
+        }
+    }
+}
+
 /// Provides access to Canvas's pixels.
 /// Returned by Canvas::access_top_layer_pixels()
 pub struct TopLayerPixels<'a> {
@@ -342,8 +384,6 @@ impl Canvas {
         }
     }
 
-    // TODO: accessTopRasterHandle()
-
     pub fn peek_pixels(&mut self) -> Option<Borrows<Pixmap>> {
         let mut pixmap = Pixmap::default();
         unsafe { self.native_mut().peekPixels(pixmap.native_mut()) }
@@ -492,6 +532,21 @@ impl Canvas {
         self
     }
 
+    /// Replaces the matrix with the matrix premultiplied with the 4x4 `matrix`.
+    ///
+    /// Unlike [`concat()`](Self::concat), the z components of the matrix are preserved, so that
+    /// further 4x4 matrices are applied to the full 3D transformation.
+    pub fn concat_44(&mut self, matrix: &Matrix44) -> &mut Self {
+        unsafe { sb::C_SkCanvas_concat44(self.native_mut(), matrix.native()) }
+        self
+    }
+
+    /// Replaces the matrix with the 4x4 `matrix`.
+    pub fn set_matrix_44(&mut self, matrix: &Matrix44) -> &mut Self {
+        unsafe { sb::C_SkCanvas_setMatrix44(self.native_mut(), matrix.native()) }
+        self
+    }
+
     pub fn clip_rect(
         &mut self,
         rect: impl AsRef<Rect>,
@@ -992,7 +1047,48 @@ impl Canvas {
         self
     }
 
-    // TODO: drawAtlas
+    /// Draws a set of sprites from `atlas`.
+    ///
+    /// Each sprite is the texture rectangle `tex[i]` of `atlas`, transformed by `xform[i]`.
+    /// If `colors` are specified, each sprite is blended with its color using the blend mode.
+    /// `cull_rect` is an optional conservative bound of all the transformed sprites.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `xform`, `tex` and `colors` do not have the same length.
+    pub fn draw_atlas(
+        &mut self,
+        atlas: impl AsRef<Image>,
+        xform: &[RSXform],
+        tex: &[Rect],
+        colors: Option<(&[Color], BlendMode)>,
+        cull_rect: Option<&Rect>,
+        paint: Option<&Paint>,
+    ) -> &mut Self {
+        let count = xform.len();
+        assert_eq!(tex.len(), count);
+        let (colors, mode) = match colors {
+            Some((colors, mode)) => {
+                assert_eq!(colors.len(), count);
+                (colors.native().as_ptr(), mode)
+            }
+            None => (ptr::null(), BlendMode::Dst),
+        };
+        unsafe {
+            sb::C_SkCanvas_drawAtlas(
+                self.native_mut(),
+                atlas.as_ref().native(),
+                xform.native().as_ptr(),
+                tex.native().as_ptr(),
+                colors,
+                count.try_into().unwrap(),
+                mode,
+                cull_rect.native_ptr_or_null(),
+                paint.native_ptr_or_null(),
+            )
+        }
+        self
+    }
 
     pub fn draw_drawable(&mut self, drawable: &mut Drawable, matrix: Option<&Matrix>) {
         unsafe {
@@ -1042,6 +1138,13 @@ impl Canvas {
         matrix
     }
 
+    /// Returns the 4x4 matrix that maps local coordinates to device coordinates.
+    pub fn local_to_device_44(&self) -> Matrix44 {
+        let mut matrix = Matrix44::default();
+        unsafe { sb::C_SkCanvas_getLocalToDevice(self.native(), matrix.native_mut()) };
+        matrix
+    }
+
     //
     // internal helper
     //
@@ -1199,8 +1302,8 @@ impl AutoCanvasRestore {
 #[cfg(test)]
 mod tests {
     use crate::{
-        AlphaType, Canvas, ClipOp, Color, ColorType, ImageInfo, Matrix, OwnedCanvas, Rect,
-        SaveLayerRec,
+        AlphaType, BlendMode, Canvas, ClipOp, Color, ColorType, ImageInfo, Matrix, Matrix44,
+        OwnedCanvas, RSXform, Rect, SaveLayerRec, Surface,
     };
 
     #[test]
@@ -1257,6 +1360,20 @@ mod tests {
         assert_ne!(Matrix::default(), total);
     }
 
+    #[test]
+    fn test_matrix_44() {
+        let mut c = Canvas::new((2, 2), None).unwrap();
+        let mut m = Matrix44::new_identity();
+        m.set_translate((1.0, 2.0, 3.0));
+        c.concat_44(&m);
+        assert!(c.local_to_device_44() == m);
+        assert_eq!(c.total_matrix(), Matrix::new_trans((1.0, 2.0)));
+        c.concat_44(&m);
+        assert!(c.local_to_device_44() == m * m);
+        c.set_matrix_44(&m);
+        assert!(c.local_to_device_44() == m);
+    }
+
     #[test]
     fn test_make_surface() {
         let mut pixels: [u32; 4] = Default::default();
@@ -1271,6 +1388,73 @@ mod tests {
         canvas.clear(Color::RED);
     }
 
+    #[test]
+    fn draw_atlas_draws_sprites() {
+        let mut atlas = Surface::new_raster_n32_premul((4, 2)).unwrap();
+        atlas.canvas().clear(Color::WHITE);
+        let atlas = atlas.image_snapshot();
+
+        let xform = [
+            RSXform::new(1.0, 0.0, (0.0, 0.0)),
+            RSXform::new(1.0, 0.0, (4.0, 4.0)),
+        ];
+        let tex = [Rect::from_wh(2.0, 2.0), Rect::new(2.0, 0.0, 4.0, 2.0)];
+
+        let mut surface = Surface::new_raster_n32_premul((8, 8)).unwrap();
+        let canvas = surface.canvas();
+        canvas.clear(Color::TRANSPARENT);
+        canvas.draw_atlas(&atlas, &xform, &tex, None, None, None);
+        let pixmap = surface.peek_pixels().unwrap();
+        assert_eq!(pixmap.get_color((0, 0)), Color::WHITE);
+        assert_eq!(pixmap.get_color((5, 4)), Color::WHITE);
+        assert_eq!(pixmap.get_color((3, 3)), Color::TRANSPARENT);
+
+        // the colors are blended with the white sprites, so the sprites are tinted.
+        let colors = [Color::RED, Color::BLUE];
+        for &mode in &[BlendMode::Modulate, BlendMode::SrcIn] {
+            let mut surface = Surface::new_raster_n32_premul((8, 8)).unwrap();
+            let canvas = surface.canvas();
+            canvas.clear(Color::TRANSPARENT);
+            canvas.draw_atlas(&atlas, &xform, &tex, Some((&colors[..], mode)), None, None);
+            let pixmap = surface.peek_pixels().unwrap();
+            assert_eq!(pixmap.get_color((0, 0)), Color::RED);
+            assert_eq!(pixmap.get_color((1, 1)), Color::RED);
+            assert_eq!(pixmap.get_color((5, 4)), Color::BLUE);
+            assert_eq!(pixmap.get_color((3, 3)), Color::TRANSPARENT);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn draw_atlas_rejects_mismatched_lengths() {
+        let mut canvas = Canvas::new((8, 8), None).unwrap();
+        let atlas = Surface::new_raster_n32_premul((4, 2))
+            .unwrap()
+            .image_snapshot();
+        let xform = [RSXform::new(1.0, 0.0, (0.0, 0.0))];
+        canvas.draw_atlas(&atlas, &xform, &[], None, None, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn draw_atlas_rejects_mismatched_color_lengths() {
+        let mut canvas = Canvas::new((8, 8), None).unwrap();
+        let atlas = Surface::new_raster_n32_premul((4, 2))
+            .unwrap()
+            .image_snapshot();
+        let xform = [RSXform::new(1.0, 0.0, (0.0, 0.0))];
+        let tex = [Rect::from_wh(2.0, 2.0)];
+        let colors = [Color::RED, Color::BLUE];
+        canvas.draw_atlas(
+            &atlas,
+            &xform,
+            &tex,
+            Some((&colors[..], BlendMode::Modulate)),
+            None,
+            None,
+        );
+    }
+
     #[test]
     fn clip_options_overloads() {
         let mut c = OwnedCanvas::default();
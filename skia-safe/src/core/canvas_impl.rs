@@ -0,0 +1,788 @@
+use crate::canvas::{
+    ImageSetEntry, Lattice, PointMode, QuadAAFlags, SaveLayerFlags, SrcRectConstraint,
+};
+use crate::prelude::*;
+use crate::utils::shadow_utils::DrawShadowRec;
+use crate::{
+    scalar, vertices, BlendMode, Canvas, ClipOp, Color, Color4f, Data, IRect, ISize, Image, Matrix,
+    Matrix44, OwnedCanvas, Paint, Path, Point, RRect, RSXform, Rect, Region, SurfaceProps,
+    TextBlob, Vertices,
+};
+use skia_bindings as sb;
+use skia_bindings::{RustCanvas_Param, TraitObject};
+use std::mem;
+
+/// The save, matrix, clip and draw calls of a canvas, implemented in Rust.
+///
+/// Create a canvas that forwards its calls to an implementation with [`Canvas::from_impl()`].
+/// The canvas does not have pixels, it maintains the matrix and clip stack and calls the
+/// implementation for every save, restore, matrix change, clip and draw call. All methods
+/// default to doing nothing.
+///
+/// Pictures and drawables are played back into the canvas, so their contents are forwarded as
+/// individual calls, and bitmaps are forwarded as images.
+///
+/// The methods are called from Skia, which can't be unwound through, so the process is aborted
+/// if one of them panics.
+#[allow(unused_variables)]
+pub trait CanvasImpl {
+    /// Called before the matrix and clip are saved, for `save()`.
+    fn will_save(&mut self) {}
+
+    /// Called before the matrix and clip are saved, for `save_layer()`. The canvas does not
+    /// create a separate layer.
+    fn will_save_layer(
+        &mut self,
+        bounds: Option<&Rect>,
+        paint: Option<&Paint>,
+        flags: SaveLayerFlags,
+    ) {
+    }
+
+    /// Called before the matrix and clip are restored.
+    fn will_restore(&mut self) {}
+
+    /// Called after the matrix is concatenated with `matrix`.
+    fn did_concat(&mut self, matrix: &Matrix) {}
+
+    /// Called after the matrix is set to `matrix`.
+    fn did_set_matrix(&mut self, matrix: &Matrix) {}
+
+    /// Called after the matrix is concatenated with the 4x4 `matrix` by
+    /// [`Canvas::concat_44()`] or [`Canvas::set_matrix_44()`].
+    fn did_concat_44(&mut self, matrix: &Matrix44) {}
+
+    fn on_draw_paint(&mut self, paint: &Paint) {}
+
+    fn on_draw_rect(&mut self, rect: &Rect, paint: &Paint) {}
+
+    fn on_draw_rrect(&mut self, rrect: &RRect, paint: &Paint) {}
+
+    fn on_draw_drrect(&mut self, outer: &RRect, inner: &RRect, paint: &Paint) {}
+
+    fn on_draw_oval(&mut self, oval: &Rect, paint: &Paint) {}
+
+    fn on_draw_arc(
+        &mut self,
+        oval: &Rect,
+        start_angle: scalar,
+        sweep_angle: scalar,
+        use_center: bool,
+        paint: &Paint,
+    ) {
+    }
+
+    fn on_draw_path(&mut self, path: &Path, paint: &Paint) {}
+
+    fn on_draw_region(&mut self, region: &Region, paint: &Paint) {}
+
+    fn on_draw_points(&mut self, mode: PointMode, pts: &[Point], paint: &Paint) {}
+
+    fn on_draw_text_blob(&mut self, blob: &TextBlob, origin: Point, paint: &Paint) {}
+
+    fn on_draw_image(&mut self, image: &Image, left_top: Point, paint: Option<&Paint>) {}
+
+    fn on_draw_image_rect(
+        &mut self,
+        image: &Image,
+        src: Option<&Rect>,
+        dst: &Rect,
+        paint: Option<&Paint>,
+        constraint: SrcRectConstraint,
+    ) {
+    }
+
+    fn on_draw_image_nine(
+        &mut self,
+        image: &Image,
+        center: &IRect,
+        dst: &Rect,
+        paint: Option<&Paint>,
+    ) {
+    }
+
+    fn on_draw_image_lattice(
+        &mut self,
+        image: &Image,
+        lattice: &Lattice,
+        dst: &Rect,
+        paint: Option<&Paint>,
+    ) {
+    }
+
+    fn on_draw_vertices(
+        &mut self,
+        vertices: &Vertices,
+        bones: &[vertices::Bone],
+        mode: BlendMode,
+        paint: &Paint,
+    ) {
+    }
+
+    fn on_draw_patch(
+        &mut self,
+        cubics: &[Point; 12],
+        colors: Option<&[Color; 4]>,
+        tex_coords: Option<&[Point; 4]>,
+        mode: BlendMode,
+        paint: &Paint,
+    ) {
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_draw_atlas(
+        &mut self,
+        atlas: &Image,
+        xform: &[RSXform],
+        tex: &[Rect],
+        colors: Option<&[Color]>,
+        mode: BlendMode,
+        cull_rect: Option<&Rect>,
+        paint: Option<&Paint>,
+    ) {
+    }
+
+    fn on_draw_annotation(&mut self, rect: &Rect, key: &str, value: Option<&Data>) {}
+
+    /// Called for shadows that are drawn with
+    /// [`draw_shadow()`](crate::utils::shadow_utils::draw_shadow).
+    fn on_draw_shadow_rec(&mut self, path: &Path, rec: &DrawShadowRec) {}
+
+    fn on_draw_edge_aa_quad(
+        &mut self,
+        rect: &Rect,
+        clip: Option<&[Point; 4]>,
+        aa_flags: QuadAAFlags,
+        color: &Color4f,
+        mode: BlendMode,
+    ) {
+    }
+
+    /// `dst_clips` contains 4 points for every entry that has a clip, and `pre_view_matrices`
+    /// the matrices the entries refer to.
+    fn on_draw_edge_aa_image_set(
+        &mut self,
+        image_set: &[ImageSetEntry],
+        dst_clips: &[Point],
+        pre_view_matrices: &[Matrix],
+        paint: Option<&Paint>,
+        constraint: SrcRectConstraint,
+    ) {
+    }
+
+    /// Called to draw `paint` behind the contents of the current layer.
+    fn on_draw_behind(&mut self, paint: &Paint) {}
+
+    /// Called before the clip is intersected with or reduced by `rect`.
+    fn on_clip_rect(&mut self, rect: &Rect, op: ClipOp, do_anti_alias: bool) {}
+
+    fn on_clip_rrect(&mut self, rrect: &RRect, op: ClipOp, do_anti_alias: bool) {}
+
+    fn on_clip_path(&mut self, path: &Path, op: ClipOp, do_anti_alias: bool) {}
+
+    /// Called with a region in device coordinates.
+    fn on_clip_region(&mut self, device_region: &Region, op: ClipOp) {}
+}
+
+impl Canvas {
+    /// Creates a canvas of `size` that forwards its calls to `canvas_impl`.
+    pub fn from_impl<'lt>(
+        size: impl Into<ISize>,
+        props: Option<&SurfaceProps>,
+        canvas_impl: impl CanvasImpl + 'lt,
+    ) -> OwnedCanvas<'lt> {
+        let size = size.into();
+        assert!(size.width >= 0 && size.height >= 0);
+        let canvas_impl: Box<dyn CanvasImpl + 'lt> = Box::new(canvas_impl);
+        let param = RustCanvas_Param {
+            trait_: unsafe { mem::transmute(canvas_impl) },
+            drop: Some(rust_canvas::drop),
+            willSave: Some(rust_canvas::will_save),
+            willSaveLayer: Some(rust_canvas::will_save_layer),
+            willRestore: Some(rust_canvas::will_restore),
+            didConcat: Some(rust_canvas::did_concat),
+            didSetMatrix: Some(rust_canvas::did_set_matrix),
+            didConcat44: Some(rust_canvas::did_concat_44),
+            drawPaint: Some(rust_canvas::draw_paint),
+            drawRect: Some(rust_canvas::draw_rect),
+            drawRRect: Some(rust_canvas::draw_rrect),
+            drawDRRect: Some(rust_canvas::draw_drrect),
+            drawOval: Some(rust_canvas::draw_oval),
+            drawArc: Some(rust_canvas::draw_arc),
+            drawPath: Some(rust_canvas::draw_path),
+            drawRegion: Some(rust_canvas::draw_region),
+            drawPoints: Some(rust_canvas::draw_points),
+            drawTextBlob: Some(rust_canvas::draw_text_blob),
+            drawImage: Some(rust_canvas::draw_image),
+            drawImageRect: Some(rust_canvas::draw_image_rect),
+            drawImageNine: Some(rust_canvas::draw_image_nine),
+            drawImageLattice: Some(rust_canvas::draw_image_lattice),
+            drawVertices: Some(rust_canvas::draw_vertices),
+            drawPatch: Some(rust_canvas::draw_patch),
+            drawAtlas: Some(rust_canvas::draw_atlas),
+            drawAnnotation: Some(rust_canvas::draw_annotation),
+            drawShadowRec: Some(rust_canvas::draw_shadow_rec),
+            drawEdgeAAQuad: Some(rust_canvas::draw_edge_aa_quad),
+            drawEdgeAAImageSet: Some(rust_canvas::draw_edge_aa_image_set),
+            drawBehind: Some(rust_canvas::draw_behind),
+            clipRect: Some(rust_canvas::clip_rect),
+            clipRRect: Some(rust_canvas::clip_rrect),
+            clipPath: Some(rust_canvas::clip_path),
+            clipRegion: Some(rust_canvas::clip_region),
+        };
+        let ptr = unsafe {
+            sb::C_RustCanvas_New(size.width, size.height, props.native_ptr_or_null(), &param)
+        };
+        Canvas::own_from_native_ptr(ptr).unwrap()
+    }
+}
+
+mod rust_canvas {
+    use super::*;
+    use crate::utils::shadow_utils::ShadowFlags;
+    use crate::Point3;
+    use skia_bindings::{
+        SkBlendMode, SkCanvas_ImageSetEntry, SkCanvas_Lattice, SkCanvas_PointMode,
+        SkCanvas_SrcRectConstraint, SkClipOp, SkColor, SkColor4f, SkData, SkIRect, SkImage,
+        SkMatrix, SkMatrix44, SkPaint, SkPath, SkPoint, SkPoint3, SkRRect, SkRSXform, SkRect,
+        SkRegion, SkTextBlob, SkVertices, SkVertices_Bone,
+    };
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::slice;
+
+    pub extern "C" fn drop(to: TraitObject) {
+        abort_on_callback_panic(|| {
+            let canvas_impl: Box<dyn CanvasImpl> = unsafe { mem::transmute(to) };
+            mem::drop(canvas_impl)
+        })
+    }
+
+    pub extern "C" fn will_save(to: TraitObject) {
+        abort_on_callback_panic(|| to_impl(to).will_save())
+    }
+
+    pub extern "C" fn will_save_layer(
+        to: TraitObject,
+        bounds: *const SkRect,
+        paint: *const SkPaint,
+        flags: u32,
+    ) {
+        abort_on_callback_panic(|| {
+            to_impl(to).will_save_layer(
+                rect_opt(bounds),
+                paint_opt(paint),
+                SaveLayerFlags::from_bits_truncate(flags),
+            )
+        })
+    }
+
+    pub extern "C" fn will_restore(to: TraitObject) {
+        abort_on_callback_panic(|| to_impl(to).will_restore())
+    }
+
+    pub extern "C" fn did_concat(to: TraitObject, matrix: *const SkMatrix) {
+        abort_on_callback_panic(|| {
+            to_impl(to).did_concat(Matrix::from_native_ref(unsafe { &*matrix }))
+        })
+    }
+
+    pub extern "C" fn did_set_matrix(to: TraitObject, matrix: *const SkMatrix) {
+        abort_on_callback_panic(|| {
+            to_impl(to).did_set_matrix(Matrix::from_native_ref(unsafe { &*matrix }))
+        })
+    }
+
+    pub extern "C" fn did_concat_44(to: TraitObject, matrix: *const SkMatrix44) {
+        abort_on_callback_panic(|| {
+            to_impl(to).did_concat_44(Matrix44::from_native_ref(unsafe { &*matrix }))
+        })
+    }
+
+    pub extern "C" fn draw_paint(to: TraitObject, paint: *const SkPaint) {
+        abort_on_callback_panic(|| to_impl(to).on_draw_paint(paint_ref(paint)))
+    }
+
+    pub extern "C" fn draw_rect(to: TraitObject, rect: *const SkRect, paint: *const SkPaint) {
+        abort_on_callback_panic(|| to_impl(to).on_draw_rect(rect_ref(rect), paint_ref(paint)))
+    }
+
+    pub extern "C" fn draw_rrect(to: TraitObject, rrect: *const SkRRect, paint: *const SkPaint) {
+        abort_on_callback_panic(|| to_impl(to).on_draw_rrect(rrect_ref(rrect), paint_ref(paint)))
+    }
+
+    pub extern "C" fn draw_drrect(
+        to: TraitObject,
+        outer: *const SkRRect,
+        inner: *const SkRRect,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            to_impl(to).on_draw_drrect(rrect_ref(outer), rrect_ref(inner), paint_ref(paint))
+        })
+    }
+
+    pub extern "C" fn draw_oval(to: TraitObject, oval: *const SkRect, paint: *const SkPaint) {
+        abort_on_callback_panic(|| to_impl(to).on_draw_oval(rect_ref(oval), paint_ref(paint)))
+    }
+
+    pub extern "C" fn draw_arc(
+        to: TraitObject,
+        oval: *const SkRect,
+        start_angle: scalar,
+        sweep_angle: scalar,
+        use_center: bool,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            to_impl(to).on_draw_arc(
+                rect_ref(oval),
+                start_angle,
+                sweep_angle,
+                use_center,
+                paint_ref(paint),
+            )
+        })
+    }
+
+    pub extern "C" fn draw_path(to: TraitObject, path: *const SkPath, paint: *const SkPaint) {
+        abort_on_callback_panic(|| {
+            to_impl(to).on_draw_path(Path::from_native_ref(unsafe { &*path }), paint_ref(paint))
+        })
+    }
+
+    pub extern "C" fn draw_region(to: TraitObject, region: *const SkRegion, paint: *const SkPaint) {
+        abort_on_callback_panic(|| {
+            to_impl(to).on_draw_region(
+                Region::from_native_ref(unsafe { &*region }),
+                paint_ref(paint),
+            )
+        })
+    }
+
+    pub extern "C" fn draw_points(
+        to: TraitObject,
+        mode: SkCanvas_PointMode,
+        count: usize,
+        pts: *const SkPoint,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let pts = unsafe { slice_or_empty(pts as *const Point, count) };
+            to_impl(to).on_draw_points(mode, pts, paint_ref(paint))
+        })
+    }
+
+    pub extern "C" fn draw_text_blob(
+        to: TraitObject,
+        blob: *const SkTextBlob,
+        x: scalar,
+        y: scalar,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let blob = blob as *mut SkTextBlob;
+            let blob = TextBlob::from_unshared_ptr_ref(&blob).as_ref().unwrap();
+            to_impl(to).on_draw_text_blob(blob, Point::new(x, y), paint_ref(paint))
+        })
+    }
+
+    pub extern "C" fn draw_image(
+        to: TraitObject,
+        image: *const SkImage,
+        left: scalar,
+        top: scalar,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let image = image as *mut SkImage;
+            let image = Image::from_unshared_ptr_ref(&image).as_ref().unwrap();
+            to_impl(to).on_draw_image(image, Point::new(left, top), paint_opt(paint))
+        })
+    }
+
+    pub extern "C" fn draw_image_rect(
+        to: TraitObject,
+        image: *const SkImage,
+        src: *const SkRect,
+        dst: *const SkRect,
+        paint: *const SkPaint,
+        constraint: SkCanvas_SrcRectConstraint,
+    ) {
+        abort_on_callback_panic(|| {
+            let image = image as *mut SkImage;
+            let image = Image::from_unshared_ptr_ref(&image).as_ref().unwrap();
+            to_impl(to).on_draw_image_rect(
+                image,
+                rect_opt(src),
+                rect_ref(dst),
+                paint_opt(paint),
+                constraint,
+            )
+        })
+    }
+
+    pub extern "C" fn draw_image_nine(
+        to: TraitObject,
+        image: *const SkImage,
+        center: *const SkIRect,
+        dst: *const SkRect,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let image = image as *mut SkImage;
+            let image = Image::from_unshared_ptr_ref(&image).as_ref().unwrap();
+            to_impl(to).on_draw_image_nine(
+                image,
+                IRect::from_native_ref(unsafe { &*center }),
+                rect_ref(dst),
+                paint_opt(paint),
+            )
+        })
+    }
+
+    pub extern "C" fn draw_image_lattice(
+        to: TraitObject,
+        image: *const SkImage,
+        lattice: *const SkCanvas_Lattice,
+        dst: *const SkRect,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let image = image as *mut SkImage;
+            let image = Image::from_unshared_ptr_ref(&image).as_ref().unwrap();
+            let lattice = unsafe { &*lattice };
+            let rect_count = ((lattice.fXCount + 1) * (lattice.fYCount + 1)) as usize;
+            let lattice = unsafe {
+                Lattice {
+                    x_divs: slice_or_empty(lattice.fXDivs, lattice.fXCount as _),
+                    y_divs: slice_or_empty(lattice.fYDivs, lattice.fYCount as _),
+                    rect_types: lattice
+                        .fRectTypes
+                        .into_option()
+                        .map(|rect_types| slice::from_raw_parts(rect_types, rect_count)),
+                    bounds: lattice
+                        .fBounds
+                        .as_ref()
+                        .map(|bounds| *IRect::from_native_ref(bounds)),
+                    colors: lattice
+                        .fColors
+                        .into_option()
+                        .map(|colors| slice::from_raw_parts(colors as *const Color, rect_count)),
+                }
+            };
+            to_impl(to).on_draw_image_lattice(image, &lattice, rect_ref(dst), paint_opt(paint))
+        })
+    }
+
+    pub extern "C" fn draw_vertices(
+        to: TraitObject,
+        vertices: *const SkVertices,
+        bones: *const SkVertices_Bone,
+        bone_count: i32,
+        mode: SkBlendMode,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let vertices = vertices as *mut SkVertices;
+            let vertices = Vertices::from_unshared_ptr_ref(&vertices).as_ref().unwrap();
+            let bones = unsafe { slice_or_empty(bones as *const vertices::Bone, bone_count as _) };
+            to_impl(to).on_draw_vertices(vertices, bones, mode, paint_ref(paint))
+        })
+    }
+
+    pub extern "C" fn draw_patch(
+        to: TraitObject,
+        cubics: *const SkPoint,
+        colors: *const SkColor,
+        tex_coords: *const SkPoint,
+        mode: SkBlendMode,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let cubics = unsafe { &*(cubics as *const [Point; 12]) };
+            let colors = unsafe { (colors as *const [Color; 4]).as_ref() };
+            let tex_coords = unsafe { (tex_coords as *const [Point; 4]).as_ref() };
+            to_impl(to).on_draw_patch(cubics, colors, tex_coords, mode, paint_ref(paint))
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub extern "C" fn draw_atlas(
+        to: TraitObject,
+        atlas: *const SkImage,
+        xform: *const SkRSXform,
+        tex: *const SkRect,
+        colors: *const SkColor,
+        count: i32,
+        mode: SkBlendMode,
+        cull_rect: *const SkRect,
+        paint: *const SkPaint,
+    ) {
+        abort_on_callback_panic(|| {
+            let atlas = atlas as *mut SkImage;
+            let atlas = Image::from_unshared_ptr_ref(&atlas).as_ref().unwrap();
+            let count = count as usize;
+            let (xform, tex, colors) = unsafe {
+                (
+                    slice_or_empty(xform as *const RSXform, count),
+                    slice_or_empty(tex as *const Rect, count),
+                    colors
+                        .into_option()
+                        .map(|colors| slice::from_raw_parts(colors as *const Color, count)),
+                )
+            };
+            to_impl(to).on_draw_atlas(
+                atlas,
+                xform,
+                tex,
+                colors,
+                mode,
+                rect_opt(cull_rect),
+                paint_opt(paint),
+            )
+        })
+    }
+
+    pub extern "C" fn draw_annotation(
+        to: TraitObject,
+        rect: *const SkRect,
+        key: *const c_char,
+        value: *mut SkData,
+    ) {
+        abort_on_callback_panic(|| {
+            let key = unsafe { CStr::from_ptr(key) }.to_string_lossy();
+            let value = Data::from_unshared_ptr_ref(&value).as_ref();
+            to_impl(to).on_draw_annotation(rect_ref(rect), &key, value)
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub extern "C" fn draw_shadow_rec(
+        to: TraitObject,
+        path: *const SkPath,
+        z_plane_params: *const SkPoint3,
+        light_pos: *const SkPoint3,
+        light_radius: scalar,
+        ambient_color: SkColor,
+        spot_color: SkColor,
+        flags: u32,
+    ) {
+        abort_on_callback_panic(|| {
+            let rec = DrawShadowRec {
+                z_plane_params: *Point3::from_native_ref(unsafe { &*z_plane_params }),
+                light_pos: *Point3::from_native_ref(unsafe { &*light_pos }),
+                light_radius,
+                ambient_color: Color::from_native(ambient_color),
+                spot_color: Color::from_native(spot_color),
+                flags: ShadowFlags::from_bits_truncate(flags),
+            };
+            to_impl(to).on_draw_shadow_rec(Path::from_native_ref(unsafe { &*path }), &rec)
+        })
+    }
+
+    pub extern "C" fn draw_edge_aa_quad(
+        to: TraitObject,
+        rect: *const SkRect,
+        clip: *const SkPoint,
+        aa_flags: u32,
+        color: *const SkColor4f,
+        mode: SkBlendMode,
+    ) {
+        abort_on_callback_panic(|| {
+            let clip = unsafe { (clip as *const [Point; 4]).as_ref() };
+            to_impl(to).on_draw_edge_aa_quad(
+                rect_ref(rect),
+                clip,
+                QuadAAFlags::from_bits_truncate(aa_flags),
+                Color4f::from_native_ref(unsafe { &*color }),
+                mode,
+            )
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub extern "C" fn draw_edge_aa_image_set(
+        to: TraitObject,
+        image_set: *const SkCanvas_ImageSetEntry,
+        count: i32,
+        dst_clips: *const SkPoint,
+        dst_clip_count: i32,
+        pre_view_matrices: *const SkMatrix,
+        matrix_count: i32,
+        paint: *const SkPaint,
+        constraint: SkCanvas_SrcRectConstraint,
+    ) {
+        abort_on_callback_panic(|| {
+            let (image_set, dst_clips, pre_view_matrices) = unsafe {
+                (
+                    slice_or_empty(image_set, count as _),
+                    slice_or_empty(dst_clips as *const Point, dst_clip_count as _),
+                    slice_or_empty(pre_view_matrices as *const Matrix, matrix_count as _),
+                )
+            };
+            let image_set: Vec<_> = image_set.iter().map(ImageSetEntry::from_native).collect();
+            to_impl(to).on_draw_edge_aa_image_set(
+                &image_set,
+                dst_clips,
+                pre_view_matrices,
+                paint_opt(paint),
+                constraint,
+            )
+        })
+    }
+
+    pub extern "C" fn draw_behind(to: TraitObject, paint: *const SkPaint) {
+        abort_on_callback_panic(|| to_impl(to).on_draw_behind(paint_ref(paint)))
+    }
+
+    pub extern "C" fn clip_rect(
+        to: TraitObject,
+        rect: *const SkRect,
+        op: SkClipOp,
+        do_anti_alias: bool,
+    ) {
+        abort_on_callback_panic(|| to_impl(to).on_clip_rect(rect_ref(rect), op, do_anti_alias))
+    }
+
+    pub extern "C" fn clip_rrect(
+        to: TraitObject,
+        rrect: *const SkRRect,
+        op: SkClipOp,
+        do_anti_alias: bool,
+    ) {
+        abort_on_callback_panic(|| to_impl(to).on_clip_rrect(rrect_ref(rrect), op, do_anti_alias))
+    }
+
+    pub extern "C" fn clip_path(
+        to: TraitObject,
+        path: *const SkPath,
+        op: SkClipOp,
+        do_anti_alias: bool,
+    ) {
+        abort_on_callback_panic(|| {
+            to_impl(to).on_clip_path(Path::from_native_ref(unsafe { &*path }), op, do_anti_alias)
+        })
+    }
+
+    pub extern "C" fn clip_region(to: TraitObject, device_region: *const SkRegion, op: SkClipOp) {
+        abort_on_callback_panic(|| {
+            to_impl(to).on_clip_region(Region::from_native_ref(unsafe { &*device_region }), op)
+        })
+    }
+
+    fn to_impl<'a>(to: TraitObject) -> &'a mut dyn CanvasImpl {
+        unsafe { mem::transmute(to) }
+    }
+
+    fn paint_ref<'a>(paint: *const SkPaint) -> &'a Paint {
+        Paint::from_native_ref(unsafe { &*paint })
+    }
+
+    fn paint_opt<'a>(paint: *const SkPaint) -> Option<&'a Paint> {
+        unsafe { paint.as_ref() }.map(Paint::from_native_ref)
+    }
+
+    fn rect_ref<'a>(rect: *const SkRect) -> &'a Rect {
+        Rect::from_native_ref(unsafe { &*rect })
+    }
+
+    fn rect_opt<'a>(rect: *const SkRect) -> Option<&'a Rect> {
+        unsafe { rect.as_ref() }.map(Rect::from_native_ref)
+    }
+
+    fn rrect_ref<'a>(rrect: *const SkRRect) -> &'a RRect {
+        RRect::from_native_ref(unsafe { &*rrect })
+    }
+
+    unsafe fn slice_or_empty<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+        if ptr.is_null() || len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(ptr, len)
+        }
+    }
+}
+
+#[test]
+fn canvas_impl_receives_draw_calls() {
+    use crate::{Font, Picture, PictureRecorder};
+
+    struct Log<'a>(&'a mut Vec<String>);
+
+    impl<'a> CanvasImpl for Log<'a> {
+        fn will_save(&mut self) {
+            self.0.push("save".into())
+        }
+
+        fn will_restore(&mut self) {
+            self.0.push("restore".into())
+        }
+
+        fn did_concat(&mut self, _matrix: &Matrix) {
+            self.0.push("concat".into())
+        }
+
+        fn on_draw_rect(&mut self, rect: &Rect, _paint: &Paint) {
+            self.0.push(format!("rect {}", rect.width()))
+        }
+
+        fn on_draw_text_blob(&mut self, _blob: &TextBlob, _origin: Point, _paint: &Paint) {
+            self.0.push("text blob".into())
+        }
+
+        fn on_draw_shadow_rec(&mut self, _path: &Path, rec: &DrawShadowRec) {
+            self.0.push(format!("shadow {}", rec.light_radius))
+        }
+
+        fn on_clip_rect(&mut self, _rect: &Rect, _op: ClipOp, _do_anti_alias: bool) {
+            self.0.push("clip".into())
+        }
+    }
+
+    let picture: Picture = {
+        let mut recorder = PictureRecorder::new();
+        let canvas = recorder.begin_recording(Rect::from_wh(10.0, 10.0), None, None);
+        canvas.draw_rect(Rect::from_wh(3.0, 3.0), &Paint::default());
+        recorder.finish_recording_as_picture(None).unwrap()
+    };
+
+    let mut log = Vec::new();
+    {
+        let mut canvas = Canvas::from_impl((100, 100), None, Log(&mut log));
+        canvas.save();
+        canvas.translate((10.0, 10.0));
+        canvas.clip_rect(Rect::from_wh(50.0, 50.0), None, None);
+        canvas.draw_rect(Rect::from_wh(20.0, 20.0), &Paint::default());
+        canvas.draw_str("Hello", (0, 0), &Font::default(), &Paint::default());
+        let mut path = Path::new();
+        path.add_rect(Rect::from_wh(5.0, 5.0), None);
+        crate::utils::shadow_utils::draw_shadow(
+            &mut *canvas,
+            &path,
+            (0.0, 0.0, 1.0),
+            (0.0, 0.0, 10.0),
+            2.0,
+            Color::BLACK,
+            Color::BLACK,
+            None,
+        );
+        canvas.restore();
+        canvas.draw_picture(&picture, None, None);
+        assert_eq!(canvas.save_count(), 1);
+    }
+    assert_eq!(
+        log[..7],
+        [
+            "save",
+            "concat",
+            "clip",
+            "rect 20",
+            "text blob",
+            "shadow 2",
+            "restore"
+        ]
+    );
+    // the picture is played back into the canvas
+    assert!(log[7..].contains(&"rect 3".to_string()));
+}
@@ -1,7 +1,9 @@
+use crate::interop::RustStream;
 use crate::prelude::*;
 use skia_bindings as sb;
 use skia_bindings::SkData;
 use std::ffi::{CStr, CString};
+use std::io;
 use std::ops::Deref;
 use std::slice;
 
@@ -97,7 +99,17 @@ impl RCHandle<SkData> {
 
     // TODO: MakeFromFileName (not sure if we need that)
     // TODO: MakeFromFile (not sure if we need that)
-    // TODO: MakeFromStream
+    /// Constructs Data by reading `size` bytes from `stream`.
+    ///
+    /// Fails with [`io::ErrorKind::UnexpectedEof`] if the stream ends before `size` bytes are
+    /// read.
+    pub fn from_stream(stream: impl io::Read + io::Seek, size: usize) -> io::Result<Data> {
+        let mut stream = RustStream::new(stream)?;
+        let data =
+            Data::from_ptr(unsafe { sb::C_SkData_MakeFromStream(stream.as_stream_mut(), size) });
+        let data = stream.error().clone().into_result(data)?;
+        data.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))
+    }
 
     pub fn new_empty() -> Self {
         Data::from_ptr(unsafe { sb::C_SkData_MakeEmpty() }).unwrap()
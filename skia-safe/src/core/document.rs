@@ -1,18 +1,24 @@
-use crate::interop::DynamicMemoryWStream;
+use crate::interop::{DynamicMemoryWStream, RustWStream};
 use crate::prelude::*;
 use crate::{Canvas, Data, Rect, Size};
-use skia_bindings::{SkDocument, SkRefCntBase};
+use skia_bindings::{SkDocument, SkRefCntBase, SkWStream};
+use std::io;
 use std::pin::Pin;
 
-pub struct Document<State = state::Open> {
+/// A document that is written to memory and returns its encoded representation when it gets
+/// closed.
+pub struct Document<State = state::Open, Stream = Pin<Box<DynamicMemoryWStream>>> {
     // note: order matters here, first the document must be
     // dropped _and then_ the stream.
     document: RCHandle<SkDocument>,
-    stream: Pin<Box<DynamicMemoryWStream>>,
+    stream: Stream,
 
     state: State,
 }
 
+/// A document that is written to an [`io::Write`] implementation while pages are added.
+pub type WriterDocument<'a, State = state::Open> = Document<State, RustWStream<'a>>;
+
 impl NativeRefCountedBase for SkDocument {
     type Base = SkRefCntBase;
 }
@@ -32,18 +38,15 @@ pub mod state {
     }
 }
 
-impl<S> Document<S> {
+impl<S, W> Document<S, W> {
     pub fn abort(mut self) {
         unsafe { self.document.native_mut().abort() }
         drop(self)
     }
 }
 
-impl Document {
-    pub(crate) fn new(
-        stream: Pin<Box<DynamicMemoryWStream>>,
-        document: RCHandle<SkDocument>,
-    ) -> Self {
+impl<W> Document<state::Open, W> {
+    pub(crate) fn new(stream: W, document: RCHandle<SkDocument>) -> Self {
         Document {
             document,
             stream,
@@ -62,7 +65,7 @@ impl Document {
         mut self,
         size: impl Into<Size>,
         content: Option<&Rect>,
-    ) -> Document<state::OnPage> {
+    ) -> Document<state::OnPage, W> {
         let size = size.into();
         let canvas = unsafe {
             self.document.native_mut().beginPage(
@@ -81,6 +84,20 @@ impl Document {
             },
         } as _
     }
+}
+
+impl Document {
+    /// Creates a document that is written to memory, `make_document` creates the native
+    /// document for the memory stream.
+    pub(crate) fn new_in_memory(
+        make_document: impl FnOnce(&mut SkWStream) -> RCHandle<SkDocument>,
+    ) -> Self {
+        // we can't move the memory stream around anymore as soon it's referred by
+        // the document.
+        let mut memory_stream = Box::pin(DynamicMemoryWStream::new());
+        let document = make_document(memory_stream.native_mut().base_mut());
+        Document::new(memory_stream, document)
+    }
 
     /// Close the document and return the encoded representation.
     /// This function consumes and drops the document.
@@ -92,7 +109,20 @@ impl Document {
     }
 }
 
-impl Document<state::OnPage> {
+impl WriterDocument<'_> {
+    /// Close the document and flush the encoded representation to the output.
+    /// This function consumes and drops the document.
+    ///
+    /// Returns the first error that occurred while writing to the output.
+    pub fn close(mut self) -> io::Result<()> {
+        unsafe {
+            self.document.native_mut().close();
+        };
+        self.stream.flush()
+    }
+}
+
+impl<W> Document<state::OnPage, W> {
     /// The current page we are currently drawing on.
     pub fn page(&self) -> usize {
         self.state.page
@@ -106,7 +136,7 @@ impl Document<state::OnPage> {
     /// Ends the page.
     /// This function consumes the document and returns a new open document that
     /// contains the pages drawn so far.
-    pub fn end_page(mut self) -> Document {
+    pub fn end_page(mut self) -> Document<state::Open, W> {
         unsafe {
             self.document.native_mut().endPage();
         }
@@ -1,9 +1,34 @@
 #[cfg(feature = "gpu")]
 use crate::gpu;
 use crate::prelude::*;
-use crate::{Canvas, Matrix, NativeFlattenable, Point, Rect};
+use crate::{Canvas, Matrix, NativeFlattenable, Picture, Point, Rect};
 use skia_bindings as sb;
-use skia_bindings::{SkDrawable, SkFlattenable, SkRefCntBase};
+use skia_bindings::{RustDrawable_Param, SkDrawable, SkFlattenable, SkRefCntBase, TraitObject};
+use std::mem;
+
+/// A drawable implemented in Rust.
+///
+/// Convert it to a [`Drawable`] with [`Drawable::new()`]. A drawable that is drawn into a
+/// [`PictureRecorder`](crate::PictureRecorder) is recorded by reference. The drawable returned
+/// by `finish_recording_as_drawable()` draws it again every time it is drawn, a picture
+/// returned by `finish_recording_as_picture()` contains a snapshot of it.
+///
+/// Call [`Drawable::notify_drawing_changed()`] when the drawing changes. The methods are called
+/// from Skia, so a panic in one of them aborts the process.
+pub trait DrawableImpl {
+    /// Returns the conservative bounds of what [`on_draw()`](Self::on_draw) draws.
+    fn on_get_bounds(&mut self) -> Rect;
+
+    /// Draws into `canvas`, the canvas' matrix and clip are restored after this returns.
+    fn on_draw(&mut self, canvas: &mut Canvas);
+
+    /// Returns a picture of the current drawing.
+    ///
+    /// If this returns `None`, the picture is recorded with [`on_draw()`](Self::on_draw).
+    fn on_new_picture_snapshot(&mut self) -> Option<Picture> {
+        None
+    }
+}
 
 pub type Drawable = RCHandle<SkDrawable>;
 
@@ -22,6 +47,19 @@ impl NativeFlattenable for SkDrawable {
 }
 
 impl RCHandle<SkDrawable> {
+    /// Creates a drawable that forwards to `drawable`.
+    pub fn new(drawable: impl DrawableImpl + 'static) -> Drawable {
+        let drawable: Box<dyn DrawableImpl> = Box::new(drawable);
+        let param = RustDrawable_Param {
+            trait_: unsafe { mem::transmute(drawable) },
+            drop: Some(rust_drawable::drop),
+            getBounds: Some(rust_drawable::get_bounds),
+            draw: Some(rust_drawable::draw),
+            newPictureSnapshot: Some(rust_drawable::new_picture_snapshot),
+        };
+        Drawable::from_ptr(unsafe { sb::C_RustDrawable_New(&param) }).unwrap()
+    }
+
     pub fn draw(&mut self, canvas: &mut Canvas, matrix: Option<&Matrix>) {
         unsafe {
             self.native_mut()
@@ -56,12 +94,10 @@ impl RCHandle<SkDrawable> {
         })
     }
 
-    // TODO: clarify ref-counter situation here, return value is SkPicture*
-    /*
     pub fn new_picture_snapshot(&mut self) -> Option<Picture> {
-        unimplemented!()
+        // newPictureSnapshot() returns a new reference.
+        Picture::from_ptr(unsafe { self.native_mut().newPictureSnapshot() })
     }
-    */
 
     pub fn generation_id(&mut self) -> u32 {
         unsafe { self.native_mut().getGenerationID() }
@@ -76,6 +112,39 @@ impl RCHandle<SkDrawable> {
     }
 }
 
+mod rust_drawable {
+    use super::*;
+    use skia_bindings::{SkCanvas, SkPicture, SkRect};
+
+    pub extern "C" fn drop(to: TraitObject) {
+        abort_on_callback_panic(|| {
+            let drawable: Box<dyn DrawableImpl> = unsafe { mem::transmute(to) };
+            mem::drop(drawable)
+        })
+    }
+
+    pub extern "C" fn get_bounds(to: TraitObject, bounds: *mut SkRect) {
+        abort_on_callback_panic(|| {
+            let rect = to_drawable(to).on_get_bounds();
+            unsafe { *bounds = rect.into_native() }
+        })
+    }
+
+    pub extern "C" fn draw(to: TraitObject, canvas: *mut SkCanvas) {
+        abort_on_callback_panic(|| {
+            to_drawable(to).on_draw(Canvas::borrow_from_native(unsafe { &mut *canvas }))
+        })
+    }
+
+    pub extern "C" fn new_picture_snapshot(to: TraitObject) -> *mut SkPicture {
+        abort_on_callback_panic(|| to_drawable(to).on_new_picture_snapshot().into_ptr_or_null())
+    }
+
+    fn to_drawable<'a>(to: TraitObject) -> &'a mut dyn DrawableImpl {
+        unsafe { mem::transmute(to) }
+    }
+}
+
 #[cfg(feature = "gpu")]
 pub use gpu_draw_handler::*;
 
@@ -102,3 +171,50 @@ pub mod gpu_draw_handler {
         }
     }
 }
+
+#[test]
+fn recorded_drawable_is_drawn_live() {
+    use crate::{Color, Paint, PictureRecorder, Surface};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Fill(Rc<Cell<Color>>);
+
+    impl DrawableImpl for Fill {
+        fn on_get_bounds(&mut self) -> Rect {
+            Rect::from_wh(2.0, 2.0)
+        }
+
+        fn on_draw(&mut self, canvas: &mut Canvas) {
+            let mut paint = Paint::default();
+            paint.set_color(self.0.get());
+            canvas.draw_rect(Rect::from_wh(2.0, 2.0), &paint);
+        }
+    }
+
+    let color = Rc::new(Cell::new(Color::RED));
+    let mut drawable = Drawable::new(Fill(color.clone()));
+    assert_eq!(drawable.bounds(), Rect::from_wh(2.0, 2.0));
+
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_wh(2.0, 2.0), None, None);
+    canvas.draw_drawable(&mut drawable, None);
+    let mut scene = recorder.finish_recording_as_drawable().unwrap();
+
+    let mut surface = Surface::new_raster_n32_premul((2, 2)).unwrap();
+    surface.canvas().draw_drawable(&mut scene, None);
+    assert_eq!(surface.peek_pixels().unwrap().get_color((1, 1)), Color::RED);
+
+    let generation_id = drawable.generation_id();
+    color.set(Color::BLUE);
+    drawable.notify_drawing_changed();
+    assert_ne!(drawable.generation_id(), generation_id);
+    surface.canvas().draw_drawable(&mut scene, None);
+    assert_eq!(
+        surface.peek_pixels().unwrap().get_color((1, 1)),
+        Color::BLUE
+    );
+
+    let snapshot = drawable.new_picture_snapshot().unwrap();
+    assert!(snapshot.approximate_op_count() > 0);
+}
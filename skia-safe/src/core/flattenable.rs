@@ -1,19 +1,26 @@
 use crate::prelude::*;
-use crate::Data;
+use crate::{Data, SerialProcs};
 use skia_bindings as sb;
 use skia_bindings::SkFlattenable;
 use std::ffi::CStr;
+use std::ptr;
 
 // TODO: getFactory()?
 // TODO: NameToFactory()?
 // TODO: FactoryToName()?
 // TODO: Register()?
 // TODO: getFlattenableType()?
-// TODO: serialize() with SkSerialProcs?
 
 pub trait Flattenable: Sized {
     fn type_name(&self) -> &CStr;
     fn serialize(&self) -> Data;
+    /// Serializes the flattenable and uses `procs` to serialize the pictures, images and
+    /// typefaces it refers to.
+    ///
+    /// The default implementation ignores `procs` and returns [`serialize()`](Self::serialize).
+    fn serialize_with_procs(&self, _procs: &mut SerialProcs) -> Data {
+        self.serialize()
+    }
     fn deserialize(data: &[u8]) -> Option<Self>;
 }
 
@@ -36,8 +43,18 @@ where
     }
 
     fn serialize(&self) -> Data {
-        Data::from_ptr(unsafe { sb::C_SkFlattenable_serialize(self.native().native_flattenable()) })
-            .unwrap()
+        Data::from_ptr(unsafe {
+            sb::C_SkFlattenable_serialize(self.native().native_flattenable(), ptr::null())
+        })
+        .unwrap()
+    }
+
+    fn serialize_with_procs(&self, procs: &mut SerialProcs) -> Data {
+        let data = Data::from_ptr(unsafe {
+            sb::C_SkFlattenable_serialize(self.native().native_flattenable(), &procs.native())
+        });
+        resume_callback_panic();
+        data.unwrap()
     }
 
     fn deserialize(data: &[u8]) -> Option<Self> {
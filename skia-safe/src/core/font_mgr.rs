@@ -5,6 +5,7 @@ use crate::{FontStyle, Typeface, Unichar};
 use skia_bindings as sb;
 use skia_bindings::{SkFontMgr, SkFontStyleSet, SkRefCntBase};
 use std::ffi::CString;
+use std::io;
 use std::mem;
 use std::os::raw::c_char;
 
@@ -190,7 +191,23 @@ impl RCHandle<SkFontMgr> {
         })
     }
 
-    // TODO: makeFromStream(.., ttcIndex).
+    /// Creates a typeface from the font data that is read from `stream`.
+    ///
+    /// The stream is read to its end before the typeface is created, because Skia needs to
+    /// open independent copies of the font data when glyphs are rasterized.
+    ///
+    /// Fails with the I/O error of the stream, or with [`io::ErrorKind::InvalidData`] if the
+    /// font data is not recognized.
+    pub fn new_from_stream(
+        &self,
+        mut stream: impl io::Read,
+        ttc_index: impl Into<Option<usize>>,
+    ) -> io::Result<Typeface> {
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes)?;
+        self.new_from_data(&bytes, ttc_index)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid font data"))
+    }
 }
 
 #[cfg(test)]
@@ -1,14 +1,16 @@
+use crate::core::surface::AsyncRead;
+use crate::core::yuva_image_generator::YUVAImageGenerator;
 #[cfg(feature = "gpu")]
 use crate::gpu;
 use crate::prelude::*;
+use crate::{surface, FilterQuality, ImageFilter, ImageGenerator, Pixmap};
 use crate::{
     AlphaType, Bitmap, ColorSpace, ColorType, Data, EncodedImageFormat, IPoint, IRect, ISize,
     ImageInfo, Matrix, Paint, Picture, Shader, TileMode,
 };
-use crate::{FilterQuality, ImageFilter, ImageGenerator, Pixmap};
 use skia_bindings as sb;
-use skia_bindings::{SkImage, SkRefCntBase};
-use std::mem;
+use skia_bindings::{SkImage, SkPixmap, SkRefCntBase};
+use std::{ffi, mem};
 
 pub use skia_bindings::SkImage_BitDepth as BitDepth;
 #[test]
@@ -40,6 +42,15 @@ impl NativeRefCountedBase for SkImage {
     type Base = SkRefCntBase;
 }
 
+type RasterRelease = Box<dyn FnOnce() + Send>;
+
+unsafe extern "C" fn raster_release_proc(_pixels: *const ffi::c_void, context: *mut ffi::c_void) {
+    abort_on_callback_panic(|| {
+        let release = Box::from_raw(context as *mut RasterRelease);
+        release()
+    })
+}
+
 impl RCHandle<SkImage> {
     // TODO: MakeRasterCopy()
 
@@ -49,7 +60,47 @@ impl RCHandle<SkImage> {
         })
     }
 
-    // TODO: MakeFromRaster()
+    /// Creates an image that shares the pixels of `buffer` without copying them.
+    ///
+    /// `buffer` is dropped when the image and all images and shaders that refer to its pixels
+    /// are dropped, or immediately if the image can not be created.
+    pub fn from_raster_buffer<B>(info: &ImageInfo, buffer: B, row_bytes: usize) -> Option<Image>
+    where
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        let buffer = Box::new(buffer);
+        let pixels: &[u8] = (*buffer).as_ref();
+        if pixels.len() < info.compute_byte_size(row_bytes) {
+            return None;
+        }
+        let pixmap = Pixmap::from_native(SkPixmap {
+            fPixels: pixels.as_ptr() as _,
+            fRowBytes: row_bytes,
+            fInfo: info.clone().into_native(),
+        });
+        unsafe { Self::from_raster(&pixmap, move || drop(buffer)) }
+    }
+
+    /// Creates an image that refers to the pixels of `pixmap` without copying them.
+    ///
+    /// `release` is called when the image and all images and shaders that refer to its pixels
+    /// are dropped, or immediately if the image can not be created.
+    ///
+    /// # Safety
+    ///
+    /// The pixels of `pixmap` must stay valid and must not be modified until `release` is
+    /// called.
+    pub unsafe fn from_raster(
+        pixmap: &Pixmap,
+        release: impl FnOnce() + Send + 'static,
+    ) -> Option<Image> {
+        let release: Box<RasterRelease> = Box::new(Box::new(release));
+        Image::from_ptr(sb::C_SkImage_MakeFromRaster(
+            pixmap.native(),
+            Some(raster_release_proc),
+            Box::into_raw(release) as _,
+        ))
+    }
 
     pub fn from_bitmap(bitmap: &Bitmap) -> Option<Image> {
         Image::from_ptr(unsafe { sb::C_SkImage_MakeFromBitmap(bitmap.native()) })
@@ -318,7 +369,75 @@ impl RCHandle<SkImage> {
         })
     }
 
-    // TODO: MakeFromYUVAPixmaps()
+    /// Creates a lazily generated image from YUV(A) planes.
+    ///
+    /// The planes are copied and converted to RGBA by Skia when the pixels are first needed.
+    /// `yuva_indices` specifies the plane and channel of Y, U, V and A, see
+    /// [`YUVAPlaneLayout`](crate::YUVAPlaneLayout) for common layouts. Planes with smaller
+    /// dimensions than `image_size` are upsampled. [`ColorType::Alpha8`] planes provide their
+    /// alpha and [`ColorType::Gray8`] planes their gray value, regardless of the channel in
+    /// `yuva_indices`.
+    ///
+    /// Returns `None` if the indices are invalid, if they do not match the number of pixmaps, or
+    /// if a plane has an unknown color type.
+    pub fn from_yuva_pixmaps(
+        yuv_color_space: crate::YUVColorSpace,
+        yuva_pixmaps: &[&Pixmap],
+        yuva_indices: &[crate::YUVAIndex; 4],
+        image_size: impl Into<ISize>,
+        image_color_space: impl Into<Option<ColorSpace>>,
+    ) -> Option<Image> {
+        let image_size = image_size.into();
+        if crate::YUVAIndex::are_valid_indices(yuva_indices)? != yuva_pixmaps.len()
+            || image_size.is_empty()
+        {
+            return None;
+        }
+        let generator = YUVAImageGenerator::new(
+            yuv_color_space,
+            yuva_pixmaps,
+            yuva_indices,
+            image_size,
+            image_color_space.into(),
+        )?;
+        Image::from_generator(ImageGenerator::new(generator), None)
+    }
+
+    /// Creates a texture backed image from YUV(A) planes by uploading them and converting them
+    /// to RGB on the GPU.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "gpu")]
+    pub fn new_texture_from_yuva_pixmaps(
+        context: &mut gpu::Context,
+        yuv_color_space: crate::YUVColorSpace,
+        yuva_pixmaps: &[&Pixmap],
+        yuva_indices: &[crate::YUVAIndex; 4],
+        image_size: impl Into<ISize>,
+        image_origin: gpu::SurfaceOrigin,
+        build_mips: bool,
+        limit_to_max_texture_size: bool,
+        image_color_space: impl Into<Option<ColorSpace>>,
+    ) -> Option<Image> {
+        if crate::YUVAIndex::are_valid_indices(yuva_indices)? != yuva_pixmaps.len() {
+            return None;
+        }
+        let yuva_pixmaps: Vec<*const SkPixmap> =
+            yuva_pixmaps.iter().map(|p| p.native() as _).collect();
+        Image::from_ptr(unsafe {
+            sb::C_SkImage_MakeFromYUVAPixmaps(
+                context.native_mut(),
+                yuv_color_space,
+                yuva_pixmaps.as_ptr(),
+                yuva_pixmaps.len(),
+                yuva_indices.native().as_ptr(),
+                image_size.into().into_native(),
+                image_origin,
+                build_mips,
+                limit_to_max_texture_size,
+                image_color_space.into().into_ptr_or_null(),
+            )
+        })
+    }
 
     #[cfg(feature = "gpu")]
     pub fn from_nv12_textures_copy(
@@ -508,6 +627,62 @@ impl RCHandle<SkImage> {
         }
     }
 
+    /// Reads `src_rect` of the image, rescales it to the dimensions of `info`, and converts it
+    /// to `info`'s color type, alpha type and color space.
+    ///
+    /// See [`Surface::async_rescale_and_read_pixels()`](crate::Surface::async_rescale_and_read_pixels).
+    pub fn async_rescale_and_read_pixels(
+        &self,
+        info: &ImageInfo,
+        src_rect: impl AsRef<IRect>,
+        rescale_gamma: surface::RescaleGamma,
+        rescale_quality: FilterQuality,
+        callback: impl FnOnce(Option<surface::AsyncReadResult>) + 'static,
+    ) {
+        unsafe {
+            sb::C_SkImage_asyncRescaleAndReadPixels(
+                self.native(),
+                info.native(),
+                src_rect.as_ref().native(),
+                rescale_gamma,
+                rescale_quality,
+                Some(AsyncRead::callback),
+                AsyncRead::new_context(AsyncRead::heights_rgba(info), callback),
+            )
+        }
+    }
+
+    /// Reads `src_rect` of the image, rescales it to `dst_size`, and converts it to Y, U and V
+    /// planes in `yuv_color_space`.
+    ///
+    /// See [`Surface::async_rescale_and_read_pixels_yuv420()`](crate::Surface::async_rescale_and_read_pixels_yuv420).
+    #[allow(clippy::too_many_arguments)]
+    pub fn async_rescale_and_read_pixels_yuv420(
+        &self,
+        yuv_color_space: crate::YUVColorSpace,
+        dst_color_space: impl Into<Option<ColorSpace>>,
+        src_rect: impl AsRef<IRect>,
+        dst_size: impl Into<ISize>,
+        rescale_gamma: surface::RescaleGamma,
+        rescale_quality: FilterQuality,
+        callback: impl FnOnce(Option<surface::AsyncReadResult>) + 'static,
+    ) {
+        let dst_size = dst_size.into();
+        unsafe {
+            sb::C_SkImage_asyncRescaleAndReadPixelsYUV420(
+                self.native(),
+                yuv_color_space,
+                dst_color_space.into().into_ptr_or_null(),
+                src_rect.as_ref().native(),
+                dst_size.native(),
+                rescale_gamma,
+                rescale_quality,
+                Some(AsyncRead::callback),
+                AsyncRead::new_context(AsyncRead::heights_yuv420(dst_size), callback),
+            )
+        }
+    }
+
     #[must_use]
     pub fn scale_pixels(
         &self,
@@ -637,3 +812,60 @@ impl RCHandle<SkImage> {
         })
     }
 }
+
+#[test]
+fn raster_buffer_is_released_when_image_is_dropped() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct Buffer(Vec<u8>, Arc<AtomicBool>);
+    impl AsRef<[u8]> for Buffer {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+    impl Drop for Buffer {
+        fn drop(&mut self) {
+            self.1.store(true, Ordering::SeqCst)
+        }
+    }
+
+    let released = Arc::new(AtomicBool::new(false));
+    let info = ImageInfo::new_n32_premul((2, 2), None);
+    let buffer = Buffer(vec![0; 16], released.clone());
+    let image = Image::from_raster_buffer(&info, buffer, 8).unwrap();
+    assert!(!released.load(Ordering::SeqCst));
+    drop(image);
+    assert!(released.load(Ordering::SeqCst));
+}
+
+#[test]
+fn yuva_pixmaps_are_converted_to_rgb() {
+    use crate::{YUVAPlaneLayout, YUVColorSpace};
+
+    let plane = |pixels: &[u8], size: i32| {
+        let info = ImageInfo::new((size, size), ColorType::Gray8, AlphaType::Opaque, None);
+        Pixmap::new(&info, pixels, size as usize)
+    };
+    // red in full range BT.601
+    let (y, u, v) = ([76u8; 16], [85u8; 4], [255u8; 4]);
+    let planes = [plane(&y, 4), plane(&u, 2), plane(&v, 2)];
+    let planes: Vec<&Pixmap> = planes.iter().map(|p| &**p).collect();
+    let layout = YUVAPlaneLayout::I420;
+    let image = Image::from_yuva_pixmaps(
+        YUVColorSpace::JPEG,
+        &planes,
+        &layout.indices(),
+        (4, 4),
+        None,
+    )
+    .unwrap();
+    assert!(image.is_opaque());
+
+    let info = ImageInfo::new((4, 4), ColorType::RGBA8888, AlphaType::Unpremul, None);
+    let mut pixels = [0u8; 4 * 4 * 4];
+    assert!(image.read_pixels(&info, &mut pixels, 16, (0, 0), CachingHint::Allow));
+    for pixel in pixels.chunks(4) {
+        assert!(pixel[0] > 250 && pixel[1] < 4 && pixel[2] < 4 && pixel[3] == 255);
+    }
+}
@@ -6,8 +6,59 @@ use crate::{
     YUVColorSpace,
 };
 use skia_bindings as sb;
-use skia_bindings::SkImageGenerator;
+use skia_bindings::{
+    RustImageGenerator_Param, SkData, SkImageGenerator, SkImageInfo, SkYUVAIndex, SkYUVASizeInfo,
+    TraitObject,
+};
 use std::ffi::c_void;
+use std::{mem, ptr, slice};
+
+/// An image generator implemented in Rust.
+///
+/// Convert it to an [`ImageGenerator`] with [`ImageGenerator::new()`] and create a lazily
+/// generated image with [`Image::from_generator()`](crate::Image::from_generator). Skia calls the
+/// generator only when the pixels are needed, and the pixels are cached like the pixels of
+/// other lazy images. Because that may happen during any draw call, a panic of the generator
+/// aborts the process.
+pub trait ImageGeneratorImpl: Send {
+    /// The info of the image that is generated. Called once when the generator is created.
+    fn info(&self) -> ImageInfo;
+
+    /// Returns the encoded image data, if the generator is backed by encoded data.
+    fn on_ref_encoded_data(&mut self) -> Option<Data> {
+        None
+    }
+
+    /// Writes the pixels of the image to `pixels`, converted to `info`.
+    ///
+    /// The dimensions of `info` match the dimensions of the generator's info, the color type,
+    /// alpha type and color space may be different. Returns `false` if the conversion is not
+    /// supported or the pixels could not be generated.
+    fn on_get_pixels(&mut self, info: &ImageInfo, pixels: &mut [u8], row_bytes: usize) -> bool;
+
+    /// Returns the plane sizes, indices and color space if the generator can produce YUVA8
+    /// planes.
+    fn on_query_yuva8(
+        &self,
+    ) -> Option<(
+        YUVASizeInfo,
+        [YUVAIndex; YUVAIndex::INDEX_COUNT],
+        YUVColorSpace,
+    )> {
+        None
+    }
+
+    /// Writes the YUVA8 planes described by [`on_query_yuva8()`](Self::on_query_yuva8) to
+    /// `planes`.
+    fn on_get_yuva8_planes(
+        &mut self,
+        _size_info: &YUVASizeInfo,
+        _yuva_indices: &[YUVAIndex; YUVAIndex::INDEX_COUNT],
+        _planes: &mut [&mut [u8]],
+    ) -> bool {
+        false
+    }
+}
 
 pub type ImageGenerator = RefHandle<SkImageGenerator>;
 
@@ -18,6 +69,22 @@ impl NativeDrop for SkImageGenerator {
 }
 
 impl RefHandle<SkImageGenerator> {
+    /// Creates an image generator that forwards to `generator`.
+    pub fn new(generator: impl ImageGeneratorImpl + 'static) -> ImageGenerator {
+        let info = generator.info();
+        let generator: Box<dyn ImageGeneratorImpl> = Box::new(generator);
+        let param = RustImageGenerator_Param {
+            trait_: unsafe { mem::transmute(generator) },
+            drop: Some(rust_image_generator::drop),
+            refEncodedData: Some(rust_image_generator::ref_encoded_data),
+            getPixels: Some(rust_image_generator::get_pixels),
+            queryYUVA8: Some(rust_image_generator::query_yuva8),
+            getYUVA8Planes: Some(rust_image_generator::get_yuva8_planes),
+        };
+        ImageGenerator::from_ptr(unsafe { sb::C_RustImageGenerator_New(info.native(), &param) })
+            .unwrap()
+    }
+
     pub fn unique_id(&self) -> u32 {
         self.native().fUniqueID
     }
@@ -134,3 +201,119 @@ impl RefHandle<SkImageGenerator> {
         })
     }
 }
+
+mod rust_image_generator {
+    use super::*;
+
+    pub extern "C" fn drop(to: TraitObject) {
+        abort_on_callback_panic(|| {
+            let generator: Box<dyn ImageGeneratorImpl> = unsafe { mem::transmute(to) };
+            mem::drop(generator)
+        })
+    }
+
+    pub extern "C" fn ref_encoded_data(to: TraitObject) -> *mut SkData {
+        abort_on_callback_panic(|| to_generator(to).on_ref_encoded_data().into_ptr_or_null())
+    }
+
+    pub extern "C" fn get_pixels(
+        to: TraitObject,
+        info: *const SkImageInfo,
+        pixels: *mut c_void,
+        row_bytes: usize,
+    ) -> bool {
+        abort_on_callback_panic(|| {
+            let info = ImageInfo::from_native_ref(unsafe { &*info });
+            let pixels = unsafe {
+                slice::from_raw_parts_mut(pixels as *mut u8, info.compute_byte_size(row_bytes))
+            };
+            to_generator(to).on_get_pixels(info, pixels, row_bytes)
+        })
+    }
+
+    pub extern "C" fn query_yuva8(
+        to: TraitObject,
+        size_info: *mut SkYUVASizeInfo,
+        indices: *mut SkYUVAIndex,
+        color_space: *mut YUVColorSpace,
+    ) -> bool {
+        abort_on_callback_panic(|| match to_generator(to).on_query_yuva8() {
+            Some((si, yuva_indices, cs)) => {
+                unsafe {
+                    *size_info = si.into_native();
+                    ptr::copy_nonoverlapping(
+                        yuva_indices.native().as_ptr(),
+                        indices,
+                        YUVAIndex::INDEX_COUNT,
+                    );
+                    *color_space = cs;
+                }
+                true
+            }
+            None => false,
+        })
+    }
+
+    pub extern "C" fn get_yuva8_planes(
+        to: TraitObject,
+        size_info: *const SkYUVASizeInfo,
+        indices: *const SkYUVAIndex,
+        planes: *mut *mut c_void,
+    ) -> bool {
+        abort_on_callback_panic(|| {
+            let size_info = YUVASizeInfo::from_native_ref(unsafe { &*size_info });
+            let indices: &[YUVAIndex; YUVAIndex::INDEX_COUNT] =
+                unsafe { &*(indices as *const [YUVAIndex; YUVAIndex::INDEX_COUNT]) };
+            let planes = unsafe { slice::from_raw_parts(planes, YUVASizeInfo::MAX_COUNT) };
+            let mut planes: Vec<&mut [u8]> = planes
+                .iter()
+                .enumerate()
+                .map(|(i, &plane)| {
+                    if plane.is_null() {
+                        return Default::default();
+                    }
+                    let height: usize = size_info.sizes[i].height.try_into().unwrap();
+                    unsafe {
+                        slice::from_raw_parts_mut(
+                            plane as *mut u8,
+                            size_info.width_bytes[i] * height,
+                        )
+                    }
+                })
+                .collect();
+            to_generator(to).on_get_yuva8_planes(size_info, indices, &mut planes)
+        })
+    }
+
+    fn to_generator<'a>(to: TraitObject) -> &'a mut dyn ImageGeneratorImpl {
+        unsafe { mem::transmute(to) }
+    }
+}
+
+#[test]
+fn rust_image_generator_is_called_lazily() {
+    use crate::Image;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Gray(Arc<AtomicUsize>);
+
+    impl ImageGeneratorImpl for Gray {
+        fn info(&self) -> ImageInfo {
+            ImageInfo::new_n32_premul((4, 4), None)
+        }
+
+        fn on_get_pixels(&mut self, _info: &ImageInfo, pixels: &mut [u8], _: usize) -> bool {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            pixels.iter_mut().for_each(|p| *p = 0x80);
+            true
+        }
+    }
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let image = Image::from_generator(ImageGenerator::new(Gray(calls.clone())), None).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    let raster = image.new_raster_image().unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(raster.width(), 4);
+}
@@ -109,6 +109,14 @@ impl Into<Matrix> for Matrix44 {
     }
 }
 
+impl From<Matrix> for Matrix44 {
+    fn from(m: Matrix) -> Self {
+        Matrix44::construct(|m44| unsafe {
+            sb::C_SkMatrix44_ConstructFromSkMatrix(m44, m.native())
+        })
+    }
+}
+
 impl ops::Mul for Matrix44 {
     type Output = Self;
 
@@ -201,6 +209,18 @@ impl Matrix44 {
 
     // TODO: asRowMajord()?
 
+    pub fn from_col_major(floats: &[scalar; 16]) -> Self {
+        let mut m = Self::new_identity();
+        m.set_col_major(floats);
+        m
+    }
+
+    pub fn from_row_major(floats: &[scalar; 16]) -> Self {
+        let mut m = Self::new_identity();
+        m.set_row_major(floats);
+        m
+    }
+
     pub fn set_col_major(&mut self, floats: &[scalar; 16]) -> &mut Self {
         unsafe { self.native_mut().setColMajorf(floats.as_ptr()) }
         self
@@ -458,6 +478,20 @@ impl Map2<(&[f64], &mut [f64])> for Matrix44 {
     }
 }
 
+#[test]
+fn matrix_conversions() {
+    let m = Matrix::new_trans((1.0, 2.0));
+    let m44 = Matrix44::from(m);
+    assert_eq!(m44.get((0, 3)), 1.0);
+    assert_eq!(m44.get((1, 3)), 2.0);
+    let m3: Matrix = m44.into();
+    assert_eq!(m3, m);
+
+    let mut col_major = [0.0; 16];
+    m44.as_col_major(&mut col_major);
+    assert!(Matrix44::from_col_major(&col_major) == m44);
+}
+
 #[test]
 fn create_identity() {
     Matrix44::new_identity();
@@ -1,8 +1,11 @@
+use crate::interop::RustStream;
 use crate::prelude::*;
-use crate::{Canvas, Data, Rect};
+use crate::{Canvas, Data, DeserialProcs, Rect, SerialProcs};
 use crate::{Matrix, Shader, TileMode};
 use skia_bindings as sb;
 use skia_bindings::{SkPicture, SkRefCntBase};
+use std::ffi::c_void;
+use std::{io, ptr};
 
 pub type Picture = RCHandle<SkPicture>;
 
@@ -11,26 +14,89 @@ impl NativeRefCountedBase for SkPicture {
 }
 
 impl RCHandle<SkPicture> {
-    // TODO: wrap MakeFromStream
-
-    // TODO: may support SkSerialProces in MakeFromData?
-
     pub fn from_data(data: &Data) -> Option<Picture> {
-        Picture::from_ptr(unsafe { sb::C_SkPicture_MakeFromData(data.native()) })
+        Picture::from_ptr(unsafe { sb::C_SkPicture_MakeFromData(data.native(), ptr::null()) })
+    }
+
+    /// Deserializes a picture that was serialized with
+    /// [`serialize_with_procs()`](Self::serialize_with_procs).
+    pub fn from_data_with_procs(data: &Data, procs: &mut DeserialProcs) -> Option<Picture> {
+        let picture = Picture::from_ptr(unsafe {
+            sb::C_SkPicture_MakeFromData(data.native(), &procs.native())
+        });
+        resume_callback_panic();
+        picture
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Option<Picture> {
         Picture::from_ptr(unsafe {
-            sb::C_SkPicture_MakeFromData2(bytes.as_ptr() as _, bytes.len())
+            sb::C_SkPicture_MakeFromData2(bytes.as_ptr() as _, bytes.len(), ptr::null())
         })
     }
 
-    // TODO: AbortCallback and the function that use it.
+    pub fn from_bytes_with_procs(bytes: &[u8], procs: &mut DeserialProcs) -> Option<Picture> {
+        let picture = Picture::from_ptr(unsafe {
+            sb::C_SkPicture_MakeFromData2(bytes.as_ptr() as _, bytes.len(), &procs.native())
+        });
+        resume_callback_panic();
+        picture
+    }
+
+    /// Reads a picture that was serialized with [`serialize()`](Self::serialize) from `stream`.
+    pub fn from_stream(stream: impl io::Read + io::Seek) -> io::Result<Picture> {
+        Self::from_stream_with_procs(stream, &mut DeserialProcs::default())
+    }
+
+    /// Reads a picture that was serialized with
+    /// [`serialize_with_procs()`](Self::serialize_with_procs) from `stream`.
+    pub fn from_stream_with_procs(
+        stream: impl io::Read + io::Seek,
+        procs: &mut DeserialProcs,
+    ) -> io::Result<Picture> {
+        let mut stream = RustStream::new(stream)?;
+        let picture = Picture::from_ptr(unsafe {
+            sb::C_SkPicture_MakeFromStream(stream.as_stream_mut(), &procs.native())
+        });
+        resume_callback_panic();
+        let picture = stream.error().clone().into_result(picture)?;
+        picture.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid picture data"))
+    }
 
     pub fn playback(&self, mut canvas: impl AsMut<Canvas>) {
         unsafe { sb::C_SkPicture_playback(self.native(), canvas.as_mut().native_mut()) }
     }
 
+    /// Replays the picture into `canvas` and calls `abort` before every drawing operation.
+    /// Playback stops as soon as `abort` returns `true`.
+    ///
+    /// Returns `true` if playback finished, and `false` if it was aborted.
+    ///
+    /// Pictures that consist of a single operation are played back without calling `abort`.
+    ///
+    /// If `abort` panics, playback is stopped and the panic is resumed.
+    pub fn playback_with_abort(
+        &self,
+        mut canvas: impl AsMut<Canvas>,
+        mut abort: impl FnMut() -> bool,
+    ) -> bool {
+        let mut aborted = false;
+        let mut callback = || {
+            aborted = abort();
+            aborted
+        };
+        let mut callback: &mut dyn FnMut() -> bool = &mut callback;
+        unsafe {
+            sb::C_SkPicture_playbackWithAbort(
+                self.native(),
+                canvas.as_mut().native_mut(),
+                Some(abort_playback),
+                &mut callback as *mut _ as *mut c_void,
+            )
+        }
+        resume_callback_panic();
+        !aborted
+    }
+
     pub fn cull_rect(&self) -> Rect {
         Rect::from_native(unsafe { sb::C_SkPicture_cullRect(self.native()) })
     }
@@ -39,10 +105,17 @@ impl RCHandle<SkPicture> {
         unsafe { sb::C_SkPicture_uniqueID(self.native()) }
     }
 
-    // TODO: support SkSerialProcs in serialize()?
-
     pub fn serialize(&self) -> Data {
-        Data::from_ptr(unsafe { sb::C_SkPicture_serialize(self.native()) }).unwrap()
+        Data::from_ptr(unsafe { sb::C_SkPicture_serialize(self.native(), ptr::null()) }).unwrap()
+    }
+
+    /// Serializes the picture and uses `procs` to serialize the pictures, images and typefaces
+    /// it contains.
+    pub fn serialize_with_procs(&self, procs: &mut SerialProcs) -> Data {
+        let data =
+            Data::from_ptr(unsafe { sb::C_SkPicture_serialize(self.native(), &procs.native()) });
+        resume_callback_panic();
+        data.unwrap()
     }
 
     pub fn new_placeholder(cull: impl AsRef<Rect>) -> Picture {
@@ -90,3 +163,55 @@ impl RCHandle<SkPicture> {
         .unwrap()
     }
 }
+
+unsafe extern "C" fn abort_playback(context: *mut c_void) -> bool {
+    catch_callback_panic(true, || {
+        let abort = &mut *(context as *mut &mut dyn FnMut() -> bool);
+        abort()
+    })
+}
+
+#[test]
+fn playback_stops_when_aborted() {
+    use crate::{Color, Paint, PictureRecorder, Surface};
+
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_wh(4.0, 4.0), None, None);
+    let mut paint = Paint::default();
+    paint.set_color(Color::RED);
+    for i in 0..4 {
+        canvas.draw_rect(Rect::from_xywh(i as f32, 0.0, 1.0, 4.0), &paint);
+    }
+    let picture = recorder.finish_recording_as_picture(None).unwrap();
+
+    let mut surface = Surface::new_raster_n32_premul((4, 4)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    let mut calls = 0;
+    let finished = picture.playback_with_abort(surface.canvas(), || {
+        calls += 1;
+        calls > 2
+    });
+    assert!(!finished);
+    assert_eq!(calls, 3);
+    let pixmap = surface.peek_pixels().unwrap();
+    assert_eq!(pixmap.get_color((3, 0)), Color::WHITE);
+
+    assert!(picture.playback_with_abort(surface.canvas(), || false));
+    let pixmap = surface.peek_pixels().unwrap();
+    assert_eq!(pixmap.get_color((3, 0)), Color::RED);
+}
+
+#[test]
+#[should_panic(expected = "abort panicked")]
+fn panics_of_abort_are_resumed() {
+    use crate::{Paint, PictureRecorder, Surface};
+
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_wh(4.0, 4.0), None, None);
+    canvas.draw_rect(Rect::from_wh(1.0, 1.0), &Paint::default());
+    canvas.draw_rect(Rect::from_wh(2.0, 2.0), &Paint::default());
+    let picture = recorder.finish_recording_as_picture(None).unwrap();
+
+    let mut surface = Surface::new_raster_n32_premul((4, 4)).unwrap();
+    picture.playback_with_abort(surface.canvas(), || panic!("abort panicked"));
+}
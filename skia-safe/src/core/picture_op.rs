@@ -0,0 +1,1044 @@
+use crate::canvas::{
+    lattice, ImageSetEntry, Lattice, PointMode, QuadAAFlags, SaveLayerFlags, SrcRectConstraint,
+};
+use crate::prelude::*;
+use crate::utils::shadow_utils;
+use crate::{
+    scalar, vertices, BlendMode, Canvas, CanvasImpl, ClipOp, Color, Color4f, Data, IRect, Image,
+    Matrix, Matrix44, Paint, Path, Point, RRect, RSXform, Rect, Region, RoundOut, SaveLayerRec,
+    TextBlob, Vertices,
+};
+use skia_bindings as sb;
+use skia_bindings::{SkCanvas_Lattice, SkPicture};
+use std::ffi::CString;
+use std::ops::Range;
+use std::ptr;
+
+/// A save, matrix, clip or draw operation of a [`Picture`](crate::Picture).
+///
+/// The operations are the calls a canvas receives when the picture is played back, so nested
+/// pictures and drawables are expanded, and bitmaps are listed as images.
+///
+/// Canvases defer saves until the matrix or clip changes, so saves that are followed only by
+/// draws are not listed, and the save and restore of the playback itself may be listed around
+/// the operations.
+#[derive(Clone)]
+pub enum PictureOp {
+    Save,
+    SaveLayer {
+        bounds: Option<Rect>,
+        paint: Option<Paint>,
+        flags: SaveLayerFlags,
+    },
+    Restore,
+    Concat(Matrix),
+    SetMatrix(Matrix),
+    Concat44(Matrix44),
+    ClipRect {
+        rect: Rect,
+        op: ClipOp,
+        do_anti_alias: bool,
+    },
+    ClipRRect {
+        rrect: RRect,
+        op: ClipOp,
+        do_anti_alias: bool,
+    },
+    ClipPath {
+        path: Path,
+        op: ClipOp,
+        do_anti_alias: bool,
+    },
+    /// A clip with a region in device coordinates.
+    ClipRegion {
+        region: Region,
+        op: ClipOp,
+    },
+    DrawPaint(Paint),
+    DrawRect {
+        rect: Rect,
+        paint: Paint,
+    },
+    DrawRRect {
+        rrect: RRect,
+        paint: Paint,
+    },
+    DrawDRRect {
+        outer: RRect,
+        inner: RRect,
+        paint: Paint,
+    },
+    DrawOval {
+        oval: Rect,
+        paint: Paint,
+    },
+    DrawArc {
+        oval: Rect,
+        start_angle: scalar,
+        sweep_angle: scalar,
+        use_center: bool,
+        paint: Paint,
+    },
+    DrawPath {
+        path: Path,
+        paint: Paint,
+    },
+    DrawRegion {
+        region: Region,
+        paint: Paint,
+    },
+    DrawPoints {
+        mode: PointMode,
+        points: Vec<Point>,
+        paint: Paint,
+    },
+    DrawTextBlob {
+        blob: TextBlob,
+        origin: Point,
+        paint: Paint,
+    },
+    DrawImage {
+        image: Image,
+        left_top: Point,
+        paint: Option<Paint>,
+    },
+    DrawImageRect {
+        image: Image,
+        src: Option<Rect>,
+        dst: Rect,
+        paint: Option<Paint>,
+        constraint: SrcRectConstraint,
+    },
+    DrawImageNine {
+        image: Image,
+        center: IRect,
+        dst: Rect,
+        paint: Option<Paint>,
+    },
+    DrawImageLattice {
+        image: Image,
+        x_divs: Vec<i32>,
+        y_divs: Vec<i32>,
+        rect_types: Option<Vec<lattice::RectType>>,
+        bounds: Option<IRect>,
+        colors: Option<Vec<Color>>,
+        dst: Rect,
+        paint: Option<Paint>,
+    },
+    DrawVertices {
+        vertices: Vertices,
+        bones: Vec<vertices::Bone>,
+        mode: BlendMode,
+        paint: Paint,
+    },
+    DrawPatch {
+        cubics: [Point; 12],
+        colors: Option<[Color; 4]>,
+        tex_coords: Option<[Point; 4]>,
+        mode: BlendMode,
+        paint: Paint,
+    },
+    DrawAtlas {
+        atlas: Image,
+        xform: Vec<RSXform>,
+        tex: Vec<Rect>,
+        colors: Option<Vec<Color>>,
+        mode: BlendMode,
+        cull_rect: Option<Rect>,
+        paint: Option<Paint>,
+    },
+    /// An annotation, annotations with keys that contain NUL characters are not played back.
+    DrawAnnotation {
+        rect: Rect,
+        key: String,
+        value: Option<Data>,
+    },
+    DrawShadowRec {
+        path: Path,
+        rec: shadow_utils::DrawShadowRec,
+    },
+    DrawEdgeAAQuad {
+        rect: Rect,
+        clip: Option<[Point; 4]>,
+        aa_flags: QuadAAFlags,
+        color: Color4f,
+        mode: BlendMode,
+    },
+    /// A set of images, `dst_clips` contains 4 points for every entry that has a clip.
+    DrawEdgeAAImageSet {
+        image_set: Vec<ImageSetEntry>,
+        dst_clips: Vec<Point>,
+        pre_view_matrices: Vec<Matrix>,
+        paint: Option<Paint>,
+        constraint: SrcRectConstraint,
+    },
+    /// Draws the paint behind the contents of the current layer.
+    DrawBehind(Paint),
+}
+
+impl PictureOp {
+    /// The name of the operation, for example `"DrawRect"`.
+    pub fn name(&self) -> &'static str {
+        use PictureOp::*;
+        match self {
+            Save => "Save",
+            SaveLayer { .. } => "SaveLayer",
+            Restore => "Restore",
+            Concat(_) => "Concat",
+            SetMatrix(_) => "SetMatrix",
+            Concat44(_) => "Concat44",
+            ClipRect { .. } => "ClipRect",
+            ClipRRect { .. } => "ClipRRect",
+            ClipPath { .. } => "ClipPath",
+            ClipRegion { .. } => "ClipRegion",
+            DrawPaint(_) => "DrawPaint",
+            DrawRect { .. } => "DrawRect",
+            DrawRRect { .. } => "DrawRRect",
+            DrawDRRect { .. } => "DrawDRRect",
+            DrawOval { .. } => "DrawOval",
+            DrawArc { .. } => "DrawArc",
+            DrawPath { .. } => "DrawPath",
+            DrawRegion { .. } => "DrawRegion",
+            DrawPoints { .. } => "DrawPoints",
+            DrawTextBlob { .. } => "DrawTextBlob",
+            DrawImage { .. } => "DrawImage",
+            DrawImageRect { .. } => "DrawImageRect",
+            DrawImageNine { .. } => "DrawImageNine",
+            DrawImageLattice { .. } => "DrawImageLattice",
+            DrawVertices { .. } => "DrawVertices",
+            DrawPatch { .. } => "DrawPatch",
+            DrawAtlas { .. } => "DrawAtlas",
+            DrawAnnotation { .. } => "DrawAnnotation",
+            DrawShadowRec { .. } => "DrawShadowRec",
+            DrawEdgeAAQuad { .. } => "DrawEdgeAAQuad",
+            DrawEdgeAAImageSet { .. } => "DrawEdgeAAImageSet",
+            DrawBehind(_) => "DrawBehind",
+        }
+    }
+
+    /// Returns `true` if the operation draws, and `false` if it changes the save stack, matrix
+    /// or clip.
+    pub fn is_draw(&self) -> bool {
+        use PictureOp::*;
+        match self {
+            Save | SaveLayer { .. } | Restore | Concat(_) | SetMatrix(_) | Concat44(_) => false,
+            ClipRect { .. } | ClipRRect { .. } | ClipPath { .. } | ClipRegion { .. } => false,
+            _ => true,
+        }
+    }
+
+    /// The bounds of the geometry of the operation in local coordinates, not including the
+    /// effects of the paint.
+    ///
+    /// Returns `None` for operations without geometry, for [`PictureOp::DrawPaint`] and
+    /// [`PictureOp::DrawBehind`], which are unbounded, and for [`PictureOp::DrawEdgeAAImageSet`],
+    /// whose entries may have their own matrices.
+    pub fn bounds(&self) -> Option<Rect> {
+        use PictureOp::*;
+        match self {
+            SaveLayer { bounds, .. } => *bounds,
+            ClipRect { rect, .. } | DrawRect { rect, .. } | DrawAnnotation { rect, .. } => {
+                Some(*rect)
+            }
+            ClipRRect { rrect, .. } | DrawRRect { rrect, .. } => Some(*rrect.rect()),
+            ClipPath { path, .. } | DrawPath { path, .. } => Some(*path.bounds()),
+            ClipRegion { region, .. } | DrawRegion { region, .. } => {
+                Some(Rect::from_irect(region.bounds()))
+            }
+            DrawDRRect { outer, .. } => Some(*outer.rect()),
+            DrawOval { oval, .. } | DrawArc { oval, .. } => Some(*oval),
+            DrawPoints { points, .. } => Rect::from_bounds(points),
+            DrawTextBlob { blob, origin, .. } => Some(blob.bounds().with_offset(*origin)),
+            DrawImage {
+                image, left_top, ..
+            } => Some(Rect::from_point_and_size(
+                *left_top,
+                (image.width() as scalar, image.height() as scalar),
+            )),
+            DrawImageRect { dst, .. }
+            | DrawImageNine { dst, .. }
+            | DrawImageLattice { dst, .. } => Some(*dst),
+            DrawVertices { vertices, .. } => Some(*vertices.bounds()),
+            DrawPatch { cubics, .. } => Rect::from_bounds(cubics),
+            DrawAtlas { cull_rect, .. } => *cull_rect,
+            DrawShadowRec { path, .. } => Some(*path.bounds()),
+            DrawEdgeAAQuad { rect, .. } => Some(*rect),
+            Save | Restore | Concat(_) | SetMatrix(_) | Concat44(_) | DrawPaint(_) => None,
+            DrawEdgeAAImageSet { .. } | DrawBehind(_) => None,
+        }
+    }
+
+    /// Plays back the operation into `canvas`.
+    ///
+    /// [`PictureOp::SetMatrix`] replaces the matrix of `canvas`, while
+    /// [`playback_ops()`](Self::playback_ops) concatenates it with the matrix `canvas` has when
+    /// the playback starts, like the playback of a picture does.
+    pub fn playback(&self, canvas: &mut Canvas) {
+        use PictureOp::*;
+        match self {
+            Save => {
+                canvas.save();
+            }
+            SaveLayer {
+                bounds,
+                paint,
+                flags,
+            } => {
+                let mut rec = SaveLayerRec::default().flags(*flags);
+                if let Some(bounds) = bounds {
+                    rec = rec.bounds(bounds);
+                }
+                if let Some(paint) = paint {
+                    rec = rec.paint(paint);
+                }
+                canvas.save_layer(&rec);
+            }
+            Restore => {
+                canvas.restore();
+            }
+            Concat(matrix) => {
+                canvas.concat(matrix);
+            }
+            SetMatrix(matrix) => {
+                canvas.set_matrix(matrix);
+            }
+            Concat44(matrix) => {
+                canvas.concat_44(matrix);
+            }
+            ClipRect {
+                rect,
+                op,
+                do_anti_alias,
+            } => {
+                canvas.clip_rect(rect, *op, *do_anti_alias);
+            }
+            ClipRRect {
+                rrect,
+                op,
+                do_anti_alias,
+            } => {
+                canvas.clip_rrect(rrect, *op, *do_anti_alias);
+            }
+            ClipPath {
+                path,
+                op,
+                do_anti_alias,
+            } => {
+                canvas.clip_path(path, *op, *do_anti_alias);
+            }
+            ClipRegion { region, op } => {
+                canvas.clip_region(region, *op);
+            }
+            DrawPaint(paint) => {
+                canvas.draw_paint(paint);
+            }
+            DrawRect { rect, paint } => {
+                canvas.draw_rect(rect, paint);
+            }
+            DrawRRect { rrect, paint } => {
+                canvas.draw_rrect(rrect, paint);
+            }
+            DrawDRRect {
+                outer,
+                inner,
+                paint,
+            } => {
+                canvas.draw_drrect(outer, inner, paint);
+            }
+            DrawOval { oval, paint } => {
+                canvas.draw_oval(oval, paint);
+            }
+            DrawArc {
+                oval,
+                start_angle,
+                sweep_angle,
+                use_center,
+                paint,
+            } => {
+                canvas.draw_arc(oval, *start_angle, *sweep_angle, *use_center, paint);
+            }
+            DrawPath { path, paint } => {
+                canvas.draw_path(path, paint);
+            }
+            DrawRegion { region, paint } => {
+                canvas.draw_region(region, paint);
+            }
+            DrawPoints {
+                mode,
+                points,
+                paint,
+            } => {
+                canvas.draw_points(*mode, points, paint);
+            }
+            DrawTextBlob {
+                blob,
+                origin,
+                paint,
+            } => {
+                canvas.draw_text_blob(blob, *origin, paint);
+            }
+            DrawImage {
+                image,
+                left_top,
+                paint,
+            } => {
+                canvas.draw_image(image, *left_top, paint.as_ref());
+            }
+            DrawImageRect {
+                image,
+                src,
+                dst,
+                paint,
+                constraint,
+            } => unsafe {
+                match src {
+                    Some(src) => canvas.native_mut().drawImageRect(
+                        image.native(),
+                        src.native(),
+                        dst.native(),
+                        paint.as_ref().native_ptr_or_null(),
+                        *constraint,
+                    ),
+                    None => canvas.native_mut().drawImageRect2(
+                        image.native(),
+                        dst.native(),
+                        paint.as_ref().native_ptr_or_null(),
+                    ),
+                }
+            },
+            DrawImageNine {
+                image,
+                center,
+                dst,
+                paint,
+            } => {
+                canvas.draw_image_nine(image, center, dst, paint.as_ref());
+            }
+            DrawImageLattice {
+                image,
+                x_divs,
+                y_divs,
+                rect_types,
+                bounds,
+                colors,
+                dst,
+                paint,
+            } => unsafe {
+                let lattice = SkCanvas_Lattice {
+                    fXDivs: x_divs.as_ptr(),
+                    fYDivs: y_divs.as_ptr(),
+                    fRectTypes: rect_types.as_ref().as_ptr_or_null(),
+                    fXCount: x_divs.len().try_into().unwrap(),
+                    fYCount: y_divs.len().try_into().unwrap(),
+                    fBounds: bounds.native().as_ptr_or_null(),
+                    fColors: colors.as_ref().as_ptr_or_null() as _,
+                };
+                canvas.native_mut().drawImageLattice(
+                    image.native(),
+                    &lattice,
+                    dst.native(),
+                    paint.as_ref().native_ptr_or_null(),
+                )
+            },
+            DrawVertices {
+                vertices,
+                bones,
+                mode,
+                paint,
+            } => {
+                let bones = if bones.is_empty() {
+                    None
+                } else {
+                    Some(&bones[..])
+                };
+                canvas.draw_vertices(vertices, bones, *mode, paint);
+            }
+            DrawPatch {
+                cubics,
+                colors,
+                tex_coords,
+                mode,
+                paint,
+            } => unsafe {
+                canvas.native_mut().drawPatch(
+                    cubics.native().as_ptr(),
+                    colors
+                        .as_ref()
+                        .map(|colors| colors.native().as_ptr())
+                        .unwrap_or(ptr::null()),
+                    tex_coords
+                        .as_ref()
+                        .map(|tex_coords| tex_coords.native().as_ptr())
+                        .unwrap_or(ptr::null()),
+                    *mode,
+                    paint.native(),
+                )
+            },
+            DrawAtlas {
+                atlas,
+                xform,
+                tex,
+                colors,
+                mode,
+                cull_rect,
+                paint,
+            } => {
+                canvas.draw_atlas(
+                    atlas,
+                    xform,
+                    tex,
+                    colors.as_ref().map(|colors| (&colors[..], *mode)),
+                    cull_rect.as_ref(),
+                    paint.as_ref(),
+                );
+            }
+            DrawAnnotation { rect, key, value } => {
+                // keys with NUL characters can't be passed to Skia.
+                if let Ok(key) = CString::new(key.as_str()) {
+                    unsafe {
+                        canvas.native_mut().drawAnnotation(
+                            rect.native(),
+                            key.as_ptr(),
+                            value
+                                .as_ref()
+                                .map(|value| value.native_mut_force())
+                                .unwrap_or(ptr::null_mut()),
+                        )
+                    }
+                }
+            }
+            DrawShadowRec { path, rec } => unsafe {
+                sb::C_SkCanvas_private_draw_shadow_rec(
+                    canvas.native_mut(),
+                    path.native(),
+                    rec.z_plane_params.native(),
+                    rec.light_pos.native(),
+                    rec.light_radius,
+                    rec.ambient_color.into_native(),
+                    rec.spot_color.into_native(),
+                    rec.flags.bits(),
+                )
+            },
+            DrawEdgeAAQuad {
+                rect,
+                clip,
+                aa_flags,
+                color,
+                mode,
+            } => unsafe {
+                sb::C_SkCanvas_experimental_DrawEdgeAAQuad(
+                    canvas.native_mut(),
+                    rect.native(),
+                    clip.as_ref()
+                        .map(|clip| clip.native().as_ptr())
+                        .unwrap_or(ptr::null()),
+                    aa_flags.bits(),
+                    color.native(),
+                    *mode,
+                )
+            },
+            DrawEdgeAAImageSet {
+                image_set,
+                dst_clips,
+                pre_view_matrices,
+                paint,
+                constraint,
+            } => {
+                let image_set: Vec<_> = image_set
+                    .iter()
+                    .map(|entry| sb::ImageSetEntryRef {
+                        image: entry.image.native(),
+                        srcRect: *entry.src_rect.native(),
+                        dstRect: *entry.dst_rect.native(),
+                        matrixIndex: entry
+                            .matrix_index
+                            .map(|index| index.try_into().unwrap())
+                            .unwrap_or(-1),
+                        alpha: entry.alpha,
+                        aaFlags: entry.aa_flags.bits(),
+                        hasClip: entry.has_clip,
+                    })
+                    .collect();
+                unsafe {
+                    sb::C_SkCanvas_experimental_DrawEdgeAAImageSet(
+                        canvas.native_mut(),
+                        image_set.as_ptr(),
+                        image_set.len().try_into().unwrap(),
+                        dst_clips.native().as_ptr(),
+                        pre_view_matrices.native().as_ptr(),
+                        paint.as_ref().native_ptr_or_null(),
+                        *constraint,
+                    )
+                }
+            }
+            DrawBehind(paint) => unsafe {
+                sb::C_SkCanvas_drawBehind(canvas.native_mut(), paint.native())
+            },
+        }
+    }
+
+    /// Plays back the draw operations in `range` of `ops` into `canvas`, usually the
+    /// [`ops()`](crate::Picture::ops) of a picture.
+    ///
+    /// All save, matrix and clip operations before the end of the range are played back, so
+    /// that the draw operations in the range are drawn like they are when all operations are
+    /// played back. [`PictureOp::SetMatrix`] is concatenated with the matrix `canvas` has when
+    /// the playback starts. The save stack of `canvas` is restored when playback ends.
+    pub fn playback_ops(ops: &[PictureOp], canvas: &mut Canvas, range: Range<usize>) {
+        let save_count = canvas.save_count();
+        let initial_matrix = canvas.total_matrix();
+        for (i, op) in ops.iter().enumerate().take(range.end) {
+            if op.is_draw() {
+                if i >= range.start {
+                    op.playback(canvas)
+                }
+            } else if let PictureOp::SetMatrix(matrix) = op {
+                canvas.set_matrix(&Matrix::concat(&initial_matrix, matrix));
+            } else {
+                op.playback(canvas)
+            }
+        }
+        canvas.restore_to_count(save_count);
+    }
+}
+
+impl RCHandle<SkPicture> {
+    /// Returns the operations of the picture, in the order they are played back.
+    ///
+    /// The operations can be played back partially with [`PictureOp::playback_ops()`].
+    pub fn ops(&self) -> Vec<PictureOp> {
+        let mut ops = Vec::new();
+        {
+            let bounds: IRect = self.cull_rect().round_out();
+            let size = (bounds.right.max(1), bounds.bottom.max(1));
+            let mut canvas = Canvas::from_impl(size, None, OpRecorder(&mut ops));
+            self.playback(&mut canvas);
+        }
+        ops
+    }
+}
+
+/// A canvas implementation that records the operations it receives.
+struct OpRecorder<'a>(&'a mut Vec<PictureOp>);
+
+impl CanvasImpl for OpRecorder<'_> {
+    fn will_save(&mut self) {
+        self.0.push(PictureOp::Save)
+    }
+
+    fn will_save_layer(
+        &mut self,
+        bounds: Option<&Rect>,
+        paint: Option<&Paint>,
+        flags: SaveLayerFlags,
+    ) {
+        self.0.push(PictureOp::SaveLayer {
+            bounds: bounds.copied(),
+            paint: paint.cloned(),
+            flags,
+        })
+    }
+
+    fn will_restore(&mut self) {
+        self.0.push(PictureOp::Restore)
+    }
+
+    fn did_concat(&mut self, matrix: &Matrix) {
+        self.0.push(PictureOp::Concat(*matrix))
+    }
+
+    fn did_set_matrix(&mut self, matrix: &Matrix) {
+        self.0.push(PictureOp::SetMatrix(*matrix))
+    }
+
+    fn did_concat_44(&mut self, matrix: &Matrix44) {
+        self.0.push(PictureOp::Concat44(*matrix))
+    }
+
+    fn on_draw_paint(&mut self, paint: &Paint) {
+        self.0.push(PictureOp::DrawPaint(paint.clone()))
+    }
+
+    fn on_draw_rect(&mut self, rect: &Rect, paint: &Paint) {
+        self.0.push(PictureOp::DrawRect {
+            rect: *rect,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_rrect(&mut self, rrect: &RRect, paint: &Paint) {
+        self.0.push(PictureOp::DrawRRect {
+            rrect: *rrect,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_drrect(&mut self, outer: &RRect, inner: &RRect, paint: &Paint) {
+        self.0.push(PictureOp::DrawDRRect {
+            outer: *outer,
+            inner: *inner,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_oval(&mut self, oval: &Rect, paint: &Paint) {
+        self.0.push(PictureOp::DrawOval {
+            oval: *oval,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_arc(
+        &mut self,
+        oval: &Rect,
+        start_angle: scalar,
+        sweep_angle: scalar,
+        use_center: bool,
+        paint: &Paint,
+    ) {
+        self.0.push(PictureOp::DrawArc {
+            oval: *oval,
+            start_angle,
+            sweep_angle,
+            use_center,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_path(&mut self, path: &Path, paint: &Paint) {
+        self.0.push(PictureOp::DrawPath {
+            path: path.clone(),
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_region(&mut self, region: &Region, paint: &Paint) {
+        self.0.push(PictureOp::DrawRegion {
+            region: region.clone(),
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_points(&mut self, mode: PointMode, pts: &[Point], paint: &Paint) {
+        self.0.push(PictureOp::DrawPoints {
+            mode,
+            points: pts.to_vec(),
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_text_blob(&mut self, blob: &TextBlob, origin: Point, paint: &Paint) {
+        self.0.push(PictureOp::DrawTextBlob {
+            blob: blob.clone(),
+            origin,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_image(&mut self, image: &Image, left_top: Point, paint: Option<&Paint>) {
+        self.0.push(PictureOp::DrawImage {
+            image: image.clone(),
+            left_top,
+            paint: paint.cloned(),
+        })
+    }
+
+    fn on_draw_image_rect(
+        &mut self,
+        image: &Image,
+        src: Option<&Rect>,
+        dst: &Rect,
+        paint: Option<&Paint>,
+        constraint: SrcRectConstraint,
+    ) {
+        self.0.push(PictureOp::DrawImageRect {
+            image: image.clone(),
+            src: src.copied(),
+            dst: *dst,
+            paint: paint.cloned(),
+            constraint,
+        })
+    }
+
+    fn on_draw_image_nine(
+        &mut self,
+        image: &Image,
+        center: &IRect,
+        dst: &Rect,
+        paint: Option<&Paint>,
+    ) {
+        self.0.push(PictureOp::DrawImageNine {
+            image: image.clone(),
+            center: *center,
+            dst: *dst,
+            paint: paint.cloned(),
+        })
+    }
+
+    fn on_draw_image_lattice(
+        &mut self,
+        image: &Image,
+        lattice: &Lattice,
+        dst: &Rect,
+        paint: Option<&Paint>,
+    ) {
+        self.0.push(PictureOp::DrawImageLattice {
+            image: image.clone(),
+            x_divs: lattice.x_divs.to_vec(),
+            y_divs: lattice.y_divs.to_vec(),
+            rect_types: lattice.rect_types.map(|rect_types| rect_types.to_vec()),
+            bounds: lattice.bounds,
+            colors: lattice.colors.map(|colors| colors.to_vec()),
+            dst: *dst,
+            paint: paint.cloned(),
+        })
+    }
+
+    fn on_draw_vertices(
+        &mut self,
+        vertices: &Vertices,
+        bones: &[vertices::Bone],
+        mode: BlendMode,
+        paint: &Paint,
+    ) {
+        self.0.push(PictureOp::DrawVertices {
+            vertices: vertices.clone(),
+            bones: bones.to_vec(),
+            mode,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_patch(
+        &mut self,
+        cubics: &[Point; 12],
+        colors: Option<&[Color; 4]>,
+        tex_coords: Option<&[Point; 4]>,
+        mode: BlendMode,
+        paint: &Paint,
+    ) {
+        self.0.push(PictureOp::DrawPatch {
+            cubics: *cubics,
+            colors: colors.copied(),
+            tex_coords: tex_coords.copied(),
+            mode,
+            paint: paint.clone(),
+        })
+    }
+
+    fn on_draw_atlas(
+        &mut self,
+        atlas: &Image,
+        xform: &[RSXform],
+        tex: &[Rect],
+        colors: Option<&[Color]>,
+        mode: BlendMode,
+        cull_rect: Option<&Rect>,
+        paint: Option<&Paint>,
+    ) {
+        self.0.push(PictureOp::DrawAtlas {
+            atlas: atlas.clone(),
+            xform: xform.to_vec(),
+            tex: tex.to_vec(),
+            colors: colors.map(|colors| colors.to_vec()),
+            mode,
+            cull_rect: cull_rect.copied(),
+            paint: paint.cloned(),
+        })
+    }
+
+    fn on_draw_annotation(&mut self, rect: &Rect, key: &str, value: Option<&Data>) {
+        self.0.push(PictureOp::DrawAnnotation {
+            rect: *rect,
+            key: key.into(),
+            value: value.cloned(),
+        })
+    }
+
+    fn on_draw_shadow_rec(&mut self, path: &Path, rec: &shadow_utils::DrawShadowRec) {
+        self.0.push(PictureOp::DrawShadowRec {
+            path: path.clone(),
+            rec: *rec,
+        })
+    }
+
+    fn on_draw_edge_aa_quad(
+        &mut self,
+        rect: &Rect,
+        clip: Option<&[Point; 4]>,
+        aa_flags: QuadAAFlags,
+        color: &Color4f,
+        mode: BlendMode,
+    ) {
+        self.0.push(PictureOp::DrawEdgeAAQuad {
+            rect: *rect,
+            clip: clip.copied(),
+            aa_flags,
+            color: color.clone(),
+            mode,
+        })
+    }
+
+    fn on_draw_edge_aa_image_set(
+        &mut self,
+        image_set: &[ImageSetEntry],
+        dst_clips: &[Point],
+        pre_view_matrices: &[Matrix],
+        paint: Option<&Paint>,
+        constraint: SrcRectConstraint,
+    ) {
+        self.0.push(PictureOp::DrawEdgeAAImageSet {
+            image_set: image_set.to_vec(),
+            dst_clips: dst_clips.to_vec(),
+            pre_view_matrices: pre_view_matrices.to_vec(),
+            paint: paint.cloned(),
+            constraint,
+        })
+    }
+
+    fn on_draw_behind(&mut self, paint: &Paint) {
+        self.0.push(PictureOp::DrawBehind(paint.clone()))
+    }
+
+    fn on_clip_rect(&mut self, rect: &Rect, op: ClipOp, do_anti_alias: bool) {
+        self.0.push(PictureOp::ClipRect {
+            rect: *rect,
+            op,
+            do_anti_alias,
+        })
+    }
+
+    fn on_clip_rrect(&mut self, rrect: &RRect, op: ClipOp, do_anti_alias: bool) {
+        self.0.push(PictureOp::ClipRRect {
+            rrect: *rrect,
+            op,
+            do_anti_alias,
+        })
+    }
+
+    fn on_clip_path(&mut self, path: &Path, op: ClipOp, do_anti_alias: bool) {
+        self.0.push(PictureOp::ClipPath {
+            path: path.clone(),
+            op,
+            do_anti_alias,
+        })
+    }
+
+    fn on_clip_region(&mut self, device_region: &Region, op: ClipOp) {
+        self.0.push(PictureOp::ClipRegion {
+            region: device_region.clone(),
+            op,
+        })
+    }
+}
+
+#[test]
+fn picture_ops_are_listed_and_played_back_by_range() {
+    use crate::{PictureRecorder, Surface};
+
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_wh(4.0, 4.0), None, None);
+    let mut red = Paint::default();
+    red.set_color(Color::RED);
+    let mut blue = Paint::default();
+    blue.set_color(Color::BLUE);
+    canvas.save();
+    canvas.translate((2.0, 0.0));
+    canvas.draw_rect(Rect::from_wh(2.0, 2.0), &red);
+    canvas.draw_rect(Rect::from_xywh(0.0, 2.0, 2.0, 2.0), &blue);
+    canvas.restore();
+    let picture = recorder.finish_recording_as_picture(None).unwrap();
+
+    let ops = picture.ops();
+    let names: Vec<&str> = ops.iter().map(PictureOp::name).collect();
+    assert_eq!(names, ["Save", "Concat", "DrawRect", "DrawRect", "Restore"]);
+    assert_eq!(ops[3].bounds(), Some(Rect::from_xywh(0.0, 2.0, 2.0, 2.0)));
+    if let PictureOp::DrawRect { paint, .. } = &ops[2] {
+        assert_eq!(paint.color(), Color::RED);
+    } else {
+        panic!("expected DrawRect")
+    }
+
+    let mut surface = Surface::new_raster_n32_premul((4, 4)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    PictureOp::playback_ops(&ops, surface.canvas(), 3..4);
+    assert_eq!(surface.canvas().save_count(), 1);
+    let pixmap = surface.peek_pixels().unwrap();
+    assert_eq!(pixmap.get_color((3, 1)), Color::WHITE);
+    assert_eq!(pixmap.get_color((3, 3)), Color::BLUE);
+}
+
+#[test]
+fn annotation_with_nul_in_key_is_not_played_back() {
+    let annotation = |key: &str| PictureOp::DrawAnnotation {
+        rect: Rect::from_wh(1.0, 1.0),
+        key: key.into(),
+        value: None,
+    };
+    let ops = [annotation("key\0"), annotation("key")];
+
+    let mut played_back = Vec::new();
+    {
+        let mut canvas = Canvas::from_impl((1, 1), None, OpRecorder(&mut played_back));
+        PictureOp::playback_ops(&ops, &mut canvas, 0..ops.len());
+    }
+    assert_eq!(played_back.len(), 1);
+    if let PictureOp::DrawAnnotation { key, .. } = &played_back[0] {
+        assert_eq!(key, "key");
+    } else {
+        panic!("expected DrawAnnotation")
+    }
+}
+
+#[test]
+fn shadow_rec_is_played_back_unchanged() {
+    let mut path = Path::new();
+    path.add_rect(Rect::from_wh(2.0, 2.0), None);
+    let mut recorded = Vec::new();
+    {
+        let mut canvas = Canvas::from_impl((4, 4), None, OpRecorder(&mut recorded));
+        canvas.translate((1.0, 1.0));
+        shadow_utils::draw_shadow(
+            &mut *canvas,
+            &path,
+            (0.0, 0.0, 1.0),
+            (2.0, 2.0, 10.0),
+            5.0,
+            Color::BLACK,
+            Color::BLACK,
+            None,
+        );
+    }
+    let shadow = |ops: &[PictureOp]| {
+        ops.iter()
+            .find_map(|op| match op {
+                PictureOp::DrawShadowRec { rec, .. } => Some(*rec),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let mut played_back = Vec::new();
+    {
+        let mut canvas = Canvas::from_impl((4, 4), None, OpRecorder(&mut played_back));
+        PictureOp::playback_ops(&recorded, &mut canvas, 0..recorded.len());
+    }
+    assert_eq!(shadow(&played_back), shadow(&recorded));
+}
+
+#[test]
+fn concat_44_is_recorded_and_played_back() {
+    let mut m = Matrix44::new_identity();
+    m.set_translate((1.0, 2.0, 3.0));
+    let mut recorded = Vec::new();
+    {
+        let mut canvas = Canvas::from_impl((4, 4), None, OpRecorder(&mut recorded));
+        canvas.concat_44(&m);
+    }
+    let names: Vec<&str> = recorded.iter().map(PictureOp::name).collect();
+    assert_eq!(names, ["Concat44"]);
+
+    let mut canvas = Canvas::new((4, 4), None).unwrap();
+    recorded[0].playback(&mut canvas);
+    assert!(canvas.local_to_device_44() == m);
+}
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::{BBHFactory, Canvas, Drawable, Picture, Rect};
+use crate::{BBHFactory, BBoxHierarchy, Canvas, Drawable, Picture, Rect};
 use skia_bindings as sb;
 use skia_bindings::{SkPictureRecorder, SkRect};
 use std::ptr;
@@ -28,7 +28,37 @@ impl Handle<SkPictureRecorder> {
         Self::from_native(unsafe { SkPictureRecorder::new() })
     }
 
-    // TODO: beginRecording with BBoxHierarchy
+    /// Begins recording a picture whose operations are indexed by `bbh`, so that playback
+    /// skips the operations that are outside of the clip of the canvas.
+    ///
+    /// # Panics
+    ///
+    /// The recorder fills `bbh` when the recording is finished, so it must not be filled
+    /// already, see [`BBoxHierarchy::insert()`].
+    pub fn begin_recording_with_bbh(
+        &mut self,
+        bounds: impl AsRef<Rect>,
+        bbh: BBoxHierarchy,
+        record_flags: impl Into<Option<RecordFlags>>,
+    ) -> &mut Canvas {
+        assert!(
+            !bbh.is_filled(),
+            "the bounding box hierarchy is already filled"
+        );
+        let canvas_ref = unsafe {
+            &mut *sb::C_SkPictureRecorder_beginRecording(
+                self.native_mut(),
+                bounds.as_ref().native(),
+                bbh.into_ptr(),
+                record_flags
+                    .into()
+                    .unwrap_or_else(RecordFlags::empty)
+                    .bits(),
+            )
+        };
+
+        Canvas::borrow_from_native(canvas_ref)
+    }
 
     pub fn begin_recording(
         &mut self,
@@ -73,3 +103,29 @@ impl Handle<SkPictureRecorder> {
         })
     }
 }
+
+#[test]
+fn playback_of_picture_with_bbh_skips_clipped_ops() {
+    use crate::{Paint, Surface};
+
+    let bounds = Rect::from_wh(1000.0, 10.0);
+    let mut recorder = PictureRecorder::new();
+    let bbh = BBoxHierarchy::new_rtree();
+    let canvas = recorder.begin_recording_with_bbh(bounds, bbh.clone(), None);
+    for i in 0..100 {
+        canvas.draw_rect(
+            Rect::from_xywh(i as f32 * 10.0, 0.0, 10.0, 10.0),
+            &Paint::default(),
+        );
+    }
+    let picture = recorder.finish_recording_as_picture(None).unwrap();
+    assert!(bbh.is_filled());
+
+    let mut surface = Surface::new_raster_n32_premul((20, 10)).unwrap();
+    let mut ops = 0;
+    picture.playback_with_abort(surface.canvas(), || {
+        ops += 1;
+        false
+    });
+    assert!(ops < 10);
+}
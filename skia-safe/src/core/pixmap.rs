@@ -8,7 +8,7 @@ use skia_bindings::SkPixmap;
 use std::convert::TryInto;
 use std::ffi::c_void;
 use std::os::raw;
-use std::{ptr, slice};
+use std::{mem, ptr, slice};
 
 pub type Pixmap = Handle<SkPixmap>;
 
@@ -41,9 +41,9 @@ impl Handle<SkPixmap> {
         assert!(pixels.len() >= height * row_bytes);
 
         let pm = Pixmap::from_native(SkPixmap {
-            fPixels: ptr::null(),
-            fRowBytes: 0,
-            fInfo: ImageInfo::new_unknown(None).native().clone(),
+            fPixels: pixels.as_ptr() as _,
+            fRowBytes: row_bytes,
+            fInfo: info.clone().into_native(),
         });
         pm.borrows(pixels)
     }
@@ -174,6 +174,74 @@ impl Handle<SkPixmap> {
     // TODO: writable_addr64
     // TODO: writable_addrF16
 
+    /// Returns the pixels as a slice of `P`, or `None` if `P` does not match the color type of
+    /// the pixmap, or if the pixels are not allocated or not aligned for `P`.
+    ///
+    /// Rows are [`row_bytes_as_pixels()`](Self::row_bytes_as_pixels) pixels apart, so the
+    /// slice includes the padding between rows, see [`rows()`](Self::rows).
+    pub fn pixels<P: Pixel>(&self) -> Option<&[P]> {
+        let (addr, len, _) = self.typed_pixels::<P>()?;
+        Some(unsafe { slice::from_raw_parts(addr, len) })
+    }
+
+    /// Returns the pixels as a mutable slice of `P`, see [`pixels()`](Self::pixels).
+    ///
+    /// # Safety
+    /// A pixmap does not own its pixels and may refer to memory that is shared or immutable,
+    /// for example the pixels of [`Pixmap::new()`] or
+    /// [`Image::peek_pixels()`](crate::Image::peek_pixels). The caller must ensure that the
+    /// pixels are writable and not accessed otherwise while the slice is alive.
+    /// [`Bitmap::pixels_mut()`](crate::Bitmap::pixels_mut) is a safe alternative.
+    pub unsafe fn pixels_mut<P: Pixel>(&mut self) -> Option<&mut [P]> {
+        let (addr, len, _) = self.typed_pixels::<P>()?;
+        Some(slice::from_raw_parts_mut(addr, len))
+    }
+
+    /// Returns an iterator over the rows of the pixmap, see [`pixels()`](Self::pixels).
+    pub fn rows<P: Pixel>(&self) -> Option<impl Iterator<Item = &[P]>> {
+        let width = self.width() as usize;
+        let (_, _, stride) = self.typed_pixels::<P>()?;
+        let pixels = self.pixels::<P>()?;
+        Some((0..self.height() as usize).map(move |y| &pixels[y * stride..y * stride + width]))
+    }
+
+    /// Returns an iterator over the mutable rows of the pixmap, see [`pixels()`](Self::pixels).
+    ///
+    /// # Safety
+    /// See [`pixels_mut()`](Self::pixels_mut).
+    pub unsafe fn rows_mut<P: Pixel>(&mut self) -> Option<impl Iterator<Item = &mut [P]>> {
+        let width = self.width() as usize;
+        let (_, _, stride) = self.typed_pixels::<P>()?;
+        let pixels = self.pixels_mut::<P>()?;
+        Some(
+            pixels
+                .chunks_mut(stride.max(1))
+                .map(move |row| &mut row[..width]),
+        )
+    }
+
+    /// Returns the address of the first pixel, the number of `P`s up to and including the last
+    /// pixel, and the number of `P`s per row, if the pixels can be accessed as `P`.
+    fn typed_pixels<P: Pixel>(&self) -> Option<(*mut P, usize, usize)> {
+        let addr = unsafe { self.addr() } as *mut P;
+        let size = mem::size_of::<P>();
+        if !P::matches_color_type(self.color_type())
+            || size != self.info().bytes_per_pixel()
+            || addr.is_null()
+            || addr as usize % mem::align_of::<P>() != 0
+            || self.row_bytes() % size != 0
+            || self.row_bytes() % mem::align_of::<P>() != 0
+        {
+            return None;
+        }
+        let stride = self.row_bytes() / size;
+        let len = match self.height() as usize {
+            0 => 0,
+            height => (height - 1) * stride + self.width() as usize,
+        };
+        Some((addr, len, stride))
+    }
+
     pub fn read_pixels<P>(
         &self,
         dst_info: &ImageInfo,
@@ -236,3 +304,121 @@ impl Handle<SkPixmap> {
         }
     }
 }
+
+/// Types that can represent a pixel of the pixels of a [`Pixmap`] or a
+/// [`Bitmap`](crate::Bitmap), see [`Pixmap::pixels()`].
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value of the type, and the type must have the size and
+/// memory layout of a pixel of all the color types for which
+/// [`matches_color_type()`](Self::matches_color_type) returns `true`.
+pub unsafe trait Pixel: Copy {
+    fn matches_color_type(ct: ColorType) -> bool;
+}
+
+unsafe impl Pixel for u8 {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::Alpha8 || ct == ColorType::Gray8
+    }
+}
+
+unsafe impl Pixel for [u8; 2] {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::R8G8UNorm
+    }
+}
+
+unsafe impl Pixel for u16 {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::RGB565 || ct == ColorType::ARGB4444 || ct == ColorType::A16UNorm
+    }
+}
+
+unsafe impl Pixel for [u16; 2] {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::R16G16UNorm
+    }
+}
+
+unsafe impl Pixel for u32 {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::RGBA8888
+            || ct == ColorType::RGB888x
+            || ct == ColorType::BGRA8888
+            || ct == ColorType::RGBA1010102
+            || ct == ColorType::RGB101010x
+    }
+}
+
+unsafe impl Pixel for [u8; 4] {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::RGBA8888 || ct == ColorType::RGB888x || ct == ColorType::BGRA8888
+    }
+}
+
+unsafe impl Pixel for u64 {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::RGBAF16Norm
+            || ct == ColorType::RGBAF16
+            || ct == ColorType::R16G16B16A16UNorm
+    }
+}
+
+unsafe impl Pixel for [u16; 4] {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::R16G16B16A16UNorm
+    }
+}
+
+unsafe impl Pixel for [f32; 4] {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::RGBAF32
+    }
+}
+
+unsafe impl Pixel for Color4f {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::RGBAF32
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Pixel for half::f16 {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::A16Float
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Pixel for [half::f16; 2] {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::R16G16Float
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Pixel for [half::f16; 4] {
+    fn matches_color_type(ct: ColorType) -> bool {
+        ct == ColorType::RGBAF16Norm || ct == ColorType::RGBAF16
+    }
+}
+
+#[test]
+fn typed_pixels_check_the_color_type_and_row_bytes() {
+    use crate::AlphaType;
+
+    let info = ImageInfo::new((3, 2), ColorType::RGBA8888, AlphaType::Unpremul, None);
+    // u32 aligned
+    let pixels = [0x1111_1111u32; 8];
+    let pixels = unsafe { slice::from_raw_parts(pixels.as_ptr() as *const u8, 32) };
+    let pixmap = Pixmap::new(&info, pixels, 16);
+    assert_eq!(pixmap.pixels::<u32>().map(|p| p.len()), Some(4 + 3));
+    assert_eq!(pixmap.rows::<[u8; 4]>().unwrap().count(), 2);
+    assert!(pixmap.rows::<[u8; 4]>().unwrap().all(|row| row.len() == 3));
+    assert!(pixmap.pixels::<u16>().is_none());
+    assert!(pixmap.pixels::<u64>().is_none());
+
+    let pixmap = Pixmap::new(&info, pixels, 14);
+    assert!(pixmap.pixels::<u32>().is_none());
+}
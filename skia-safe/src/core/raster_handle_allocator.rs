@@ -1 +1,204 @@
-// TODO
+use crate::prelude::*;
+use crate::{Canvas, IRect, ImageInfo, Matrix, OwnedCanvas};
+use skia_bindings as sb;
+use skia_bindings::{RustRasterHandleAllocator_Param, TraitObject};
+use std::ffi::c_void;
+use std::{mem, ptr};
+
+/// An opaque handle that a [`RasterHandleAllocator`] associates with the pixel memory of a
+/// layer, for example a native OS bitmap or a shared memory segment.
+pub type RasterHandle = *mut c_void;
+
+/// The pixel memory of a layer, allocated by [`RasterHandleAllocator::alloc_handle()`].
+pub struct RasterHandleRec {
+    /// The address of the first pixel.
+    pub pixels: *mut c_void,
+    pub row_bytes: usize,
+    /// The handle that is passed to [`RasterHandleAllocator::update_handle()`] and returned by
+    /// [`Canvas::access_top_raster_handle()`] while the layer is the top layer.
+    pub handle: RasterHandle,
+    /// Called when the layer is destroyed and the pixel memory is not used anymore.
+    pub release: Option<Box<dyn FnOnce()>>,
+}
+
+/// Allocates the pixel memory of the base layer and of every layer that is created by
+/// `save_layer()` on a canvas created with [`Canvas::from_raster_handle_allocator()`].
+///
+/// The allocator and the release functions are called from Skia, if they panic, the process is
+/// aborted.
+///
+/// # Safety
+///
+/// The pixel memory returned by [`alloc_handle()`](Self::alloc_handle) must be valid for reads
+/// and writes of `row_bytes * info.height()` bytes, and must stay valid until its release
+/// function is called or, if there is none, until the canvas is dropped.
+pub unsafe trait RasterHandleAllocator {
+    /// Allocates the pixel memory of a layer described by `info`.
+    ///
+    /// Return `None` if the memory can not be allocated, in which case the layer is not created.
+    fn alloc_handle(&mut self, info: &ImageInfo) -> Option<RasterHandleRec>;
+
+    /// Called before the canvas passes `handle` to the caller of
+    /// [`Canvas::access_top_raster_handle()`], so that the native handle can be updated with the
+    /// current matrix and device clip bounds.
+    fn update_handle(&mut self, handle: RasterHandle, matrix: &Matrix, clip_bounds: &IRect);
+}
+
+impl Canvas {
+    /// Creates a raster canvas of `info` whose base layer and layers are allocated by
+    /// `allocator`.
+    ///
+    /// Returns `None` if `info` is not supported by raster canvases or if the allocation of the
+    /// base layer fails.
+    pub fn from_raster_handle_allocator<'lt>(
+        allocator: impl RasterHandleAllocator + 'lt,
+        info: &ImageInfo,
+    ) -> Option<OwnedCanvas<'lt>> {
+        let allocator: Box<dyn RasterHandleAllocator + 'lt> = Box::new(allocator);
+        let param = RustRasterHandleAllocator_Param {
+            trait_: unsafe { mem::transmute(allocator) },
+            drop: Some(rust_raster_handle_allocator::drop),
+            allocHandle: Some(rust_raster_handle_allocator::alloc_handle),
+            updateHandle: Some(rust_raster_handle_allocator::update_handle),
+        };
+        Canvas::own_from_native_ptr(unsafe {
+            sb::C_RustRasterHandleAllocator_MakeCanvas(info.native(), &param)
+        })
+    }
+
+    /// Returns the handle of the top layer after the allocator updated it with the current
+    /// matrix and clip, or `None` if the canvas was not created with
+    /// [`Canvas::from_raster_handle_allocator()`].
+    pub fn access_top_raster_handle(&self) -> Option<RasterHandle> {
+        unsafe { self.native().accessTopRasterHandle() }.into_option()
+    }
+}
+
+mod rust_raster_handle_allocator {
+    use super::*;
+    use skia_bindings::{SkIRect, SkImageInfo, SkMatrix, SkRasterHandleAllocator_Rec};
+
+    type Release = Box<dyn FnOnce()>;
+
+    pub extern "C" fn drop(to: TraitObject) {
+        abort_on_callback_panic(|| {
+            let allocator: Box<dyn RasterHandleAllocator> = unsafe { mem::transmute(to) };
+            mem::drop(allocator)
+        })
+    }
+
+    pub extern "C" fn alloc_handle(
+        to: TraitObject,
+        info: *const SkImageInfo,
+        rec: *mut SkRasterHandleAllocator_Rec,
+    ) -> bool {
+        abort_on_callback_panic(|| {
+            let info = ImageInfo::from_native_ref(unsafe { &*info });
+            match to_allocator(to).alloc_handle(info) {
+                Some(allocated) => {
+                    let rec = unsafe { &mut *rec };
+                    rec.fPixels = allocated.pixels;
+                    rec.fRowBytes = allocated.row_bytes;
+                    rec.fHandle = allocated.handle;
+                    match allocated.release {
+                        Some(release) => {
+                            let release: Box<Release> = Box::new(release);
+                            rec.fReleaseProc = Some(release_pixels);
+                            rec.fReleaseCtx = Box::into_raw(release) as _;
+                        }
+                        None => {
+                            rec.fReleaseProc = None;
+                            rec.fReleaseCtx = ptr::null_mut();
+                        }
+                    }
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+
+    pub extern "C" fn update_handle(
+        to: TraitObject,
+        handle: *mut c_void,
+        matrix: *const SkMatrix,
+        clip_bounds: *const SkIRect,
+    ) {
+        abort_on_callback_panic(|| {
+            to_allocator(to).update_handle(
+                handle,
+                Matrix::from_native_ref(unsafe { &*matrix }),
+                IRect::from_native_ref(unsafe { &*clip_bounds }),
+            )
+        })
+    }
+
+    unsafe extern "C" fn release_pixels(_pixels: *mut c_void, context: *mut c_void) {
+        abort_on_callback_panic(|| {
+            let release = Box::from_raw(context as *mut Release);
+            release()
+        })
+    }
+
+    fn to_allocator<'a>(to: TraitObject) -> &'a mut dyn RasterHandleAllocator {
+        unsafe { mem::transmute(to) }
+    }
+}
+
+#[test]
+fn layers_are_allocated_by_the_allocator() {
+    use crate::{Paint, Rect};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Default)]
+    struct Layers {
+        allocated: usize,
+        released: usize,
+        updated: Vec<IRect>,
+    }
+
+    struct Allocator(Rc<RefCell<Layers>>);
+
+    unsafe impl RasterHandleAllocator for Allocator {
+        fn alloc_handle(&mut self, info: &ImageInfo) -> Option<RasterHandleRec> {
+            let row_bytes = info.min_row_bytes();
+            let mut pixels = vec![0u8; info.compute_byte_size(row_bytes)].into_boxed_slice();
+            let pixels_ptr = pixels.as_mut_ptr() as *mut c_void;
+            let mut layers = self.0.borrow_mut();
+            layers.allocated += 1;
+            let layers_rc = self.0.clone();
+            Some(RasterHandleRec {
+                pixels: pixels_ptr,
+                row_bytes,
+                handle: layers.allocated as RasterHandle,
+                release: Some(Box::new(move || {
+                    mem::drop(pixels);
+                    layers_rc.borrow_mut().released += 1;
+                })),
+            })
+        }
+
+        fn update_handle(&mut self, _handle: RasterHandle, _matrix: &Matrix, clip_bounds: &IRect) {
+            self.0.borrow_mut().updated.push(*clip_bounds);
+        }
+    }
+
+    let layers = Rc::new(RefCell::new(Layers::default()));
+    {
+        let info = ImageInfo::new_n32_premul((4, 4), None);
+        let mut canvas =
+            Canvas::from_raster_handle_allocator(Allocator(layers.clone()), &info).unwrap();
+        assert_eq!(canvas.access_top_raster_handle(), Some(1 as RasterHandle));
+        canvas.save_layer(&Default::default());
+        canvas.draw_rect(Rect::from_wh(2.0, 2.0), &Paint::default());
+        assert_eq!(canvas.access_top_raster_handle(), Some(2 as RasterHandle));
+        canvas.restore();
+        assert_eq!(layers.borrow().released, 1);
+        assert_eq!(canvas.access_top_raster_handle(), Some(1 as RasterHandle));
+    }
+    let layers = layers.borrow();
+    assert_eq!(layers.allocated, 2);
+    assert_eq!(layers.released, 2);
+    assert_eq!(layers.updated.len(), 3);
+}
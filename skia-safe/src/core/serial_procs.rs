@@ -0,0 +1,225 @@
+use crate::prelude::*;
+use crate::{Data, Image, Picture, Typeface};
+use skia_bindings::{RustDeserialProcs, RustSerialProcs};
+use std::ffi::c_void;
+use std::ptr;
+
+type SerialProc<'a, T> = Box<dyn FnMut(&T) -> Option<Data> + 'a>;
+type DeserialProc<'a, T> = Box<dyn FnMut(&[u8]) -> Option<T> + 'a>;
+
+/// Procs that replace the serialization of the pictures, images and typefaces that are
+/// embedded in a serialized picture or flattenable.
+///
+/// If a proc is not set or returns `None`, the object is serialized with its default
+/// serialization. If a proc panics, the object is serialized with its default serialization,
+/// too, and the panic is resumed after the serialization.
+#[derive(Default)]
+pub struct SerialProcs<'a> {
+    picture: Option<SerialProc<'a, Picture>>,
+    image: Option<SerialProc<'a, Image>>,
+    typeface: Option<SerialProc<'a, Typeface>>,
+}
+
+impl<'a> SerialProcs<'a> {
+    pub fn set_picture_proc(
+        &mut self,
+        proc: impl FnMut(&Picture) -> Option<Data> + 'a,
+    ) -> &mut Self {
+        self.picture = Some(Box::new(proc));
+        self
+    }
+
+    pub fn set_image_proc(&mut self, proc: impl FnMut(&Image) -> Option<Data> + 'a) -> &mut Self {
+        self.image = Some(Box::new(proc));
+        self
+    }
+
+    pub fn set_typeface_proc(
+        &mut self,
+        proc: impl FnMut(&Typeface) -> Option<Data> + 'a,
+    ) -> &mut Self {
+        self.typeface = Some(Box::new(proc));
+        self
+    }
+
+    /// The native procs, which refer to `self` and must not outlive it.
+    pub(crate) fn native(&mut self) -> RustSerialProcs {
+        RustSerialProcs {
+            picture: self.picture.as_ref().map(|_| serialize_picture as _),
+            image: self.image.as_ref().map(|_| serialize_image as _),
+            typeface: self.typeface.as_ref().map(|_| serialize_typeface as _),
+            context: self as *mut Self as _,
+        }
+    }
+}
+
+/// Procs that deserialize the pictures, images and typefaces that were serialized with
+/// [`SerialProcs`].
+///
+/// The picture and typeface procs are only called for data that was written by the
+/// corresponding serial proc. The image proc is called for every image, if it is not set or
+/// returns `None`, the data is decoded as an encoded image. A panic of a proc is resumed after
+/// the deserialization.
+#[derive(Default)]
+pub struct DeserialProcs<'a> {
+    picture: Option<DeserialProc<'a, Picture>>,
+    image: Option<DeserialProc<'a, Image>>,
+    typeface: Option<DeserialProc<'a, Typeface>>,
+}
+
+impl<'a> DeserialProcs<'a> {
+    pub fn set_picture_proc(
+        &mut self,
+        proc: impl FnMut(&[u8]) -> Option<Picture> + 'a,
+    ) -> &mut Self {
+        self.picture = Some(Box::new(proc));
+        self
+    }
+
+    pub fn set_image_proc(&mut self, proc: impl FnMut(&[u8]) -> Option<Image> + 'a) -> &mut Self {
+        self.image = Some(Box::new(proc));
+        self
+    }
+
+    pub fn set_typeface_proc(
+        &mut self,
+        proc: impl FnMut(&[u8]) -> Option<Typeface> + 'a,
+    ) -> &mut Self {
+        self.typeface = Some(Box::new(proc));
+        self
+    }
+
+    /// The native procs, which refer to `self` and must not outlive it.
+    pub(crate) fn native(&mut self) -> RustDeserialProcs {
+        RustDeserialProcs {
+            picture: self.picture.as_ref().map(|_| deserialize_picture as _),
+            image: self.image.as_ref().map(|_| deserialize_image as _),
+            typeface: self.typeface.as_ref().map(|_| deserialize_typeface as _),
+            context: self as *mut Self as _,
+        }
+    }
+}
+
+unsafe extern "C" fn serialize_picture(
+    context: *mut c_void,
+    picture: *mut skia_bindings::SkPicture,
+) -> *mut skia_bindings::SkData {
+    catch_callback_panic(ptr::null_mut(), || {
+        let procs = &mut *(context as *mut SerialProcs);
+        let picture = Picture::from_unshared_ptr_ref(&picture).as_ref().unwrap();
+        (procs.picture.as_mut().unwrap())(picture).into_ptr_or_null()
+    })
+}
+
+unsafe extern "C" fn serialize_image(
+    context: *mut c_void,
+    image: *mut skia_bindings::SkImage,
+) -> *mut skia_bindings::SkData {
+    catch_callback_panic(ptr::null_mut(), || {
+        let procs = &mut *(context as *mut SerialProcs);
+        let image = Image::from_unshared_ptr_ref(&image).as_ref().unwrap();
+        (procs.image.as_mut().unwrap())(image).into_ptr_or_null()
+    })
+}
+
+unsafe extern "C" fn serialize_typeface(
+    context: *mut c_void,
+    typeface: *mut skia_bindings::SkTypeface,
+) -> *mut skia_bindings::SkData {
+    catch_callback_panic(ptr::null_mut(), || {
+        let procs = &mut *(context as *mut SerialProcs);
+        let typeface = Typeface::from_unshared_ptr_ref(&typeface).as_ref().unwrap();
+        (procs.typeface.as_mut().unwrap())(typeface).into_ptr_or_null()
+    })
+}
+
+unsafe extern "C" fn deserialize_picture(
+    context: *mut c_void,
+    data: *const c_void,
+    length: usize,
+) -> *mut skia_bindings::SkPicture {
+    catch_callback_panic(ptr::null_mut(), || {
+        let procs = &mut *(context as *mut DeserialProcs);
+        (procs.picture.as_mut().unwrap())(bytes(data, length)).into_ptr_or_null()
+    })
+}
+
+unsafe extern "C" fn deserialize_image(
+    context: *mut c_void,
+    data: *const c_void,
+    length: usize,
+) -> *mut skia_bindings::SkImage {
+    catch_callback_panic(ptr::null_mut(), || {
+        let procs = &mut *(context as *mut DeserialProcs);
+        (procs.image.as_mut().unwrap())(bytes(data, length)).into_ptr_or_null()
+    })
+}
+
+unsafe extern "C" fn deserialize_typeface(
+    context: *mut c_void,
+    data: *const c_void,
+    length: usize,
+) -> *mut skia_bindings::SkTypeface {
+    catch_callback_panic(ptr::null_mut(), || {
+        let procs = &mut *(context as *mut DeserialProcs);
+        (procs.typeface.as_mut().unwrap())(bytes(data, length)).into_ptr_or_null()
+    })
+}
+
+unsafe fn bytes<'a>(data: *const c_void, length: usize) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data as *const u8, length)
+    }
+}
+
+#[test]
+fn images_are_serialized_by_reference() {
+    use crate::{PictureRecorder, Rect, Surface};
+
+    let mut surface = Surface::new_raster_n32_premul((16, 16)).unwrap();
+    let image = surface.image_snapshot();
+
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_wh(16.0, 16.0), None, None);
+    canvas.draw_image(&image, (0, 0), None);
+    let picture = recorder.finish_recording_as_picture(None).unwrap();
+
+    let mut serialized_images = 0;
+    let data = picture.serialize_with_procs(SerialProcs::default().set_image_proc(|_| {
+        serialized_images += 1;
+        Some(Data::new_copy(b"image-ref"))
+    }));
+    assert_eq!(serialized_images, 1);
+    assert!(data.len() < picture.serialize().len());
+
+    let mut references = Vec::new();
+    let deserialized = Picture::from_data_with_procs(
+        &data,
+        DeserialProcs::default().set_image_proc(|bytes| {
+            references.push(bytes.to_vec());
+            Some(image.clone())
+        }),
+    );
+    assert!(deserialized.is_some());
+    assert_eq!(references, vec![b"image-ref".to_vec()]);
+}
+
+#[test]
+#[should_panic(expected = "image proc panicked")]
+fn panics_of_procs_are_resumed() {
+    use crate::{PictureRecorder, Rect, Surface};
+
+    let mut surface = Surface::new_raster_n32_premul((16, 16)).unwrap();
+    let image = surface.image_snapshot();
+
+    let mut recorder = PictureRecorder::new();
+    let canvas = recorder.begin_recording(Rect::from_wh(16.0, 16.0), None, None);
+    canvas.draw_image(&image, (0, 0), None);
+    let picture = recorder.finish_recording_as_picture(None).unwrap();
+
+    picture.serialize_with_procs(
+        SerialProcs::default().set_image_proc(|_| panic!("image proc panicked")),
+    );
+}
@@ -2,12 +2,12 @@
 use crate::gpu;
 use crate::prelude::*;
 use crate::{
-    Bitmap, Canvas, DeferredDisplayList, IPoint, IRect, ISize, Image, ImageInfo, Paint, Pixmap,
-    Size, SurfaceCharacterization, SurfaceProps,
+    Bitmap, Canvas, ColorSpace, DeferredDisplayList, FilterQuality, IPoint, IRect, ISize, Image,
+    ImageInfo, Paint, Pixmap, Size, SurfaceCharacterization, SurfaceProps, YUVColorSpace,
 };
 use skia_bindings as sb;
-use skia_bindings::{SkRefCntBase, SkSurface};
-use std::ptr;
+use skia_bindings::{SkRefCntBase, SkSurface, SkSurface_AsyncReadResult};
+use std::{ffi, ptr, slice};
 
 pub use skia_bindings::SkSurface_ContentChangeMode as ContentChangeMode;
 #[test]
@@ -21,12 +21,110 @@ fn test_surface_backend_handle_access_naming() {
     let _ = BackendHandleAccess::FlushWrite;
 }
 
+pub use skia_bindings::SkSurface_RescaleGamma as RescaleGamma;
+#[test]
+fn test_surface_rescale_gamma_naming() {
+    let _ = RescaleGamma::Linear;
+}
+
+/// The pixels of an asynchronous read.
+///
+/// Holds one plane for [`Surface::async_rescale_and_read_pixels()`], and the Y, U and V planes
+/// for [`Surface::async_rescale_and_read_pixels_yuv420()`].
+pub struct AsyncReadResult {
+    native: RefHandle<SkSurface_AsyncReadResult>,
+    heights: [usize; 3],
+}
+
+impl NativeDrop for SkSurface_AsyncReadResult {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkSurface_AsyncReadResult_delete(self) }
+    }
+}
+
+impl AsyncReadResult {
+    /// The number of planes.
+    pub fn count(&self) -> usize {
+        unsafe { sb::C_SkSurface_AsyncReadResult_count(self.native.native()) }
+            .try_into()
+            .unwrap()
+    }
+
+    /// The pixels of the plane `i`, `row_bytes(i)` bytes per row.
+    pub fn data(&self, i: usize) -> &[u8] {
+        assert!(i < self.count());
+        unsafe {
+            let data = sb::C_SkSurface_AsyncReadResult_data(self.native.native(), i as _);
+            slice::from_raw_parts(data as *const u8, self.row_bytes(i) * self.heights[i])
+        }
+    }
+
+    pub fn row_bytes(&self, i: usize) -> usize {
+        assert!(i < self.count());
+        unsafe { sb::C_SkSurface_AsyncReadResult_rowBytes(self.native.native(), i as _) }
+    }
+}
+
+/// The Rust side of an asynchronous read, passed to Skia as the `ReadPixelsContext`.
+pub(crate) struct AsyncRead {
+    heights: [usize; 3],
+    callback: Box<dyn FnOnce(Option<AsyncReadResult>)>,
+}
+
+impl AsyncRead {
+    pub(crate) fn new_context(
+        heights: [usize; 3],
+        callback: impl FnOnce(Option<AsyncReadResult>) + 'static,
+    ) -> *mut ffi::c_void {
+        Box::into_raw(Box::new(AsyncRead {
+            heights,
+            callback: Box::new(callback),
+        })) as _
+    }
+
+    pub(crate) fn heights_rgba(info: &ImageInfo) -> [usize; 3] {
+        [info.height().try_into().unwrap(), 0, 0]
+    }
+
+    pub(crate) fn heights_yuv420(dst_size: ISize) -> [usize; 3] {
+        let chroma_height = ((dst_size.height + 1) / 2).try_into().unwrap();
+        [
+            dst_size.height.try_into().unwrap(),
+            chroma_height,
+            chroma_height,
+        ]
+    }
+
+    pub(crate) unsafe extern "C" fn callback(
+        context: *mut ffi::c_void,
+        result: *const SkSurface_AsyncReadResult,
+    ) {
+        abort_on_callback_panic(|| {
+            let read = Box::from_raw(context as *mut AsyncRead);
+            let result = RefHandle::from_ptr(result as *mut _).map(|native| AsyncReadResult {
+                native,
+                heights: read.heights,
+            });
+            (read.callback)(result)
+        })
+    }
+}
+
 pub type Surface = RCHandle<SkSurface>;
 
 impl NativeRefCountedBase for SkSurface {
     type Base = SkRefCntBase;
 }
 
+type RasterRelease = Box<dyn FnOnce() + Send>;
+
+unsafe extern "C" fn raster_release_proc(_pixels: *mut ffi::c_void, context: *mut ffi::c_void) {
+    abort_on_callback_panic(|| {
+        let release = Box::from_raw(context as *mut RasterRelease);
+        release()
+    })
+}
+
 impl RCHandle<SkSurface> {
     pub fn new_raster_direct<'pixels>(
         image_info: &ImageInfo,
@@ -53,7 +151,65 @@ impl RCHandle<SkSurface> {
         .map(move |surface| surface.borrows(pixels))
     }
 
-    // TODO: MakeRasterDirectReleaseProc()?
+    /// Creates a raster surface that draws into the pixels of `buffer` without copying them.
+    ///
+    /// `buffer` is dropped when the surface and all images that share its pixels are dropped,
+    /// or immediately if the surface can not be created.
+    pub fn new_raster_from_buffer<B>(
+        image_info: &ImageInfo,
+        buffer: B,
+        row_bytes: impl Into<Option<usize>>,
+        surface_props: Option<&SurfaceProps>,
+    ) -> Option<Surface>
+    where
+        B: AsMut<[u8]> + Send + 'static,
+    {
+        let row_bytes = row_bytes
+            .into()
+            .unwrap_or_else(|| image_info.min_row_bytes());
+        let mut buffer = Box::new(buffer);
+        let pixels = (*buffer).as_mut();
+        if pixels.len() < image_info.compute_byte_size(row_bytes) {
+            return None;
+        }
+        let pixels = pixels.as_mut_ptr();
+        unsafe {
+            Self::new_raster_direct_with_release(
+                image_info,
+                pixels,
+                row_bytes,
+                move || drop(buffer),
+                surface_props,
+            )
+        }
+    }
+
+    /// Creates a raster surface that draws into `pixels` without copying them.
+    ///
+    /// `release` is called when the surface and all images that share its pixels are dropped,
+    /// or immediately if the surface can not be created.
+    ///
+    /// # Safety
+    ///
+    /// `pixels` must point to a buffer large enough to hold an image described by `image_info`
+    /// and `row_bytes`, and must stay valid until `release` is called.
+    pub unsafe fn new_raster_direct_with_release(
+        image_info: &ImageInfo,
+        pixels: *mut u8,
+        row_bytes: usize,
+        release: impl FnOnce() + Send + 'static,
+        surface_props: Option<&SurfaceProps>,
+    ) -> Option<Surface> {
+        let release: Box<RasterRelease> = Box::new(Box::new(release));
+        Self::from_ptr(sb::C_SkSurface_MakeRasterDirectReleaseProc(
+            image_info.native(),
+            pixels as _,
+            row_bytes,
+            Some(raster_release_proc),
+            Box::into_raw(release) as _,
+            surface_props.native_ptr_or_null(),
+        ))
+    }
 
     pub fn new_raster(
         image_info: &ImageInfo,
@@ -457,9 +613,68 @@ impl RCHandle<SkSurface> {
         unsafe { self.native_mut().readPixels2(bitmap.native(), src.x, src.y) }
     }
 
-    // TODO: wrap AsyncReadResult (m79)
-    // TODO: wrap asyncRescaleAndReadPixels (m76/m79)
-    // TODO: wrap asyncRescaleAndReadPixelsYUV420 (m77/m79)
+    /// Reads `src_rect` of the surface, rescales it to the dimensions of `info`, and converts it
+    /// to `info`'s color type, alpha type and color space.
+    ///
+    /// `callback` receives the pixels or `None` if the read failed. On GPU surfaces, it is called
+    /// when the read has completed after a later flush, on raster surfaces it is called before
+    /// this function returns.
+    pub fn async_rescale_and_read_pixels(
+        &mut self,
+        info: &ImageInfo,
+        src_rect: impl AsRef<IRect>,
+        rescale_gamma: RescaleGamma,
+        rescale_quality: FilterQuality,
+        callback: impl FnOnce(Option<AsyncReadResult>) + 'static,
+    ) {
+        unsafe {
+            sb::C_SkSurface_asyncRescaleAndReadPixels(
+                self.native_mut(),
+                info.native(),
+                src_rect.as_ref().native(),
+                rescale_gamma,
+                rescale_quality,
+                Some(AsyncRead::callback),
+                AsyncRead::new_context(AsyncRead::heights_rgba(info), callback),
+            )
+        }
+    }
+
+    /// Reads `src_rect` of the surface, rescales it to `dst_size`, and converts it to Y, U and
+    /// V planes in `yuv_color_space`. The U and V planes are subsampled by a factor of two in
+    /// both directions.
+    ///
+    /// `callback` receives the planes or `None` if the read failed, see
+    /// [`async_rescale_and_read_pixels()`](Self::async_rescale_and_read_pixels).
+    ///
+    /// Skia implements this read only for GPU surfaces. On raster surfaces, the rescaled pixels
+    /// are read as RGBA and converted to the planes with Skia's RGB to YUV color matrix.
+    #[allow(clippy::too_many_arguments)]
+    pub fn async_rescale_and_read_pixels_yuv420(
+        &mut self,
+        yuv_color_space: YUVColorSpace,
+        dst_color_space: impl Into<Option<ColorSpace>>,
+        src_rect: impl AsRef<IRect>,
+        dst_size: impl Into<ISize>,
+        rescale_gamma: RescaleGamma,
+        rescale_quality: FilterQuality,
+        callback: impl FnOnce(Option<AsyncReadResult>) + 'static,
+    ) {
+        let dst_size = dst_size.into();
+        unsafe {
+            sb::C_SkSurface_asyncRescaleAndReadPixelsYUV420(
+                self.native_mut(),
+                yuv_color_space,
+                dst_color_space.into().into_ptr_or_null(),
+                src_rect.as_ref().native(),
+                dst_size.native(),
+                rescale_gamma,
+                rescale_quality,
+                Some(AsyncRead::callback),
+                AsyncRead::new_context(AsyncRead::heights_yuv420(dst_size), callback),
+            )
+        }
+    }
 
     pub fn write_pixels_from_pixmap(&mut self, src: &Pixmap, dst: impl Into<IPoint>) {
         let dst = dst.into();
@@ -525,3 +740,46 @@ fn test_raster_direct() {
     let paint = Paint::default();
     surface.canvas().draw_circle((10, 10), 10.0, &paint);
 }
+
+#[test]
+fn async_rescale_and_read_pixels_completes_on_raster_surfaces() {
+    use crate::{AlphaType, Color, ColorType};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut surface = Surface::new_raster_n32_premul((8, 8)).unwrap();
+    surface.canvas().clear(Color::RED);
+    let info = ImageInfo::new((4, 4), ColorType::RGBA8888, AlphaType::Premul, None);
+
+    let pixels = Rc::new(RefCell::new(None));
+    let result = pixels.clone();
+    surface.async_rescale_and_read_pixels(
+        &info,
+        IRect::from_wh(8, 8),
+        RescaleGamma::Src,
+        FilterQuality::Low,
+        move |r| *result.borrow_mut() = r.map(|r| r.data(0).to_vec()),
+    );
+    let pixels = pixels.borrow_mut().take().unwrap();
+    assert_eq!(pixels.len(), 4 * 4 * 4);
+    assert_eq!(&pixels[..4], &[0xff, 0, 0, 0xff]);
+
+    let planes = Rc::new(RefCell::new(None));
+    let result = planes.clone();
+    surface.async_rescale_and_read_pixels_yuv420(
+        YUVColorSpace::Rec601,
+        None,
+        IRect::from_wh(8, 8),
+        (4, 4),
+        RescaleGamma::Src,
+        FilterQuality::Low,
+        move |r| {
+            *result.borrow_mut() =
+                r.map(|r| (r.count(), r.data(1).len() / r.row_bytes(1), r.data(0)[0]))
+        },
+    );
+    let (count, chroma_rows, y) = planes.borrow_mut().take().unwrap();
+    assert_eq!((count, chroma_rows), (3, 2));
+    // red in limited range BT.601
+    assert!((80..=83).contains(&y));
+}
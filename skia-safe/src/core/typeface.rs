@@ -4,7 +4,7 @@ use crate::{font_arguments, interop, FontArguments};
 use crate::{font_parameters::VariationAxis, Data, FontStyle, GlyphId, Rect, Unichar};
 use skia_bindings as sb;
 use skia_bindings::{SkRefCntBase, SkTypeface, SkTypeface_LocalizedStrings};
-use std::{ffi, ptr};
+use std::{ffi, io, ptr};
 
 pub type FontId = skia_bindings::SkFontID;
 pub type FontTableTag = skia_bindings::SkFontTableTag;
@@ -113,8 +113,6 @@ impl RCHandle<SkTypeface> {
     // from_file is unsupported, because it is unclear what the
     // encoding of the path name is. from_data can be used instead.
 
-    // TODO: MakeFromStream()?
-
     pub fn from_data(data: Data, index: impl Into<Option<usize>>) -> Option<Typeface> {
         Typeface::from_ptr(unsafe {
             sb::C_SkTypeface_MakeFromData(
@@ -124,6 +122,23 @@ impl RCHandle<SkTypeface> {
         })
     }
 
+    /// Creates a typeface from the font data that is read from `stream`.
+    ///
+    /// The stream is read to its end before the typeface is created, because Skia needs to
+    /// open independent copies of the font data when glyphs are rasterized.
+    ///
+    /// Fails with the I/O error of the stream, or with [`io::ErrorKind::InvalidData`] if the
+    /// font data is not recognized.
+    pub fn from_stream(
+        mut stream: impl io::Read,
+        index: impl Into<Option<usize>>,
+    ) -> io::Result<Typeface> {
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes)?;
+        Typeface::from_data(Data::new_copy(&bytes), index)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid font data"))
+    }
+
     pub fn clone_with_arguments(&self, arguments: &FontArguments) -> Option<Typeface> {
         Typeface::from_ptr(unsafe { sb::C_SkTypeface_makeClone(self.native(), arguments.native()) })
     }
@@ -0,0 +1,160 @@
+//! A generator for images from YUVA planes.
+//!
+//! `SkImage::MakeFromYUVAPixmaps()` requires a GPU context in m81, so raster images from YUVA
+//! pixmaps are created lazily with this generator instead. The planes are passed to Skia, which
+//! converts them to RGB on the GPU, or with its YUV to RGB color matrix on the CPU.
+
+use crate::prelude::*;
+use crate::yuva_index::Index;
+use crate::{
+    AlphaType, ColorSpace, ColorType, ISize, ImageGeneratorImpl, ImageInfo, Pixmap, YUVAIndex,
+    YUVASizeInfo, YUVColorSpace,
+};
+use skia_bindings as sb;
+use skia_bindings::SkPixmap;
+use std::slice;
+
+struct Plane {
+    info: ImageInfo,
+    pixels: Vec<u8>,
+    row_bytes: usize,
+}
+
+impl Plane {
+    fn copy_from(pixmap: &Pixmap) -> Option<Plane> {
+        if pixmap.color_type() == ColorType::Unknown || unsafe { pixmap.addr() }.is_null() {
+            return None;
+        }
+        let pixels = unsafe {
+            slice::from_raw_parts(pixmap.addr() as *const u8, pixmap.compute_byte_size())
+        };
+        // the last row may not be padded, but Pixmap::new() expects all rows to be.
+        let mut pixels = pixels.to_vec();
+        pixels.resize(pixmap.height() as usize * pixmap.row_bytes(), 0);
+        Some(Plane {
+            info: pixmap.info().clone(),
+            pixels,
+            row_bytes: pixmap.row_bytes(),
+        })
+    }
+
+    /// The number of bytes of a row without padding.
+    fn width_bytes(&self) -> usize {
+        self.info.min_row_bytes()
+    }
+
+    /// Returns `true` if the plane has the memory layout of a YUVA8 plane. BGRA planes are
+    /// excluded, because Skia interprets 4 byte planes as RGBA.
+    fn is_yuva8(&self) -> bool {
+        match self.info.color_type() {
+            ColorType::Alpha8 | ColorType::Gray8 | ColorType::R8G8UNorm | ColorType::RGBA8888 => {
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+pub(crate) struct YUVAImageGenerator {
+    info: ImageInfo,
+    yuv_color_space: YUVColorSpace,
+    planes: Vec<Plane>,
+    yuva_indices: [YUVAIndex; YUVAIndex::INDEX_COUNT],
+}
+
+// The color spaces of the infos are only shared with immutable `SkColorSpace` instances.
+unsafe impl Send for YUVAImageGenerator {}
+
+impl YUVAImageGenerator {
+    /// Copies the planes, returns `None` if a plane has an unknown color type or no pixels.
+    pub fn new(
+        yuv_color_space: YUVColorSpace,
+        yuva_pixmaps: &[&Pixmap],
+        yuva_indices: &[YUVAIndex; YUVAIndex::INDEX_COUNT],
+        image_size: ISize,
+        image_color_space: Option<ColorSpace>,
+    ) -> Option<YUVAImageGenerator> {
+        let planes = yuva_pixmaps
+            .iter()
+            .map(|pixmap| Plane::copy_from(pixmap))
+            .collect::<Option<Vec<_>>>()?;
+
+        let alpha_type = if yuva_indices[Index::A as usize].is_valid() {
+            AlphaType::Premul
+        } else {
+            AlphaType::Opaque
+        };
+        let info = ImageInfo::new(image_size, ColorType::n32(), alpha_type, image_color_space);
+
+        Some(YUVAImageGenerator {
+            info,
+            yuv_color_space,
+            planes,
+            yuva_indices: *yuva_indices,
+        })
+    }
+}
+
+impl ImageGeneratorImpl for YUVAImageGenerator {
+    fn info(&self) -> ImageInfo {
+        self.info.clone()
+    }
+
+    fn on_get_pixels(&mut self, info: &ImageInfo, pixels: &mut [u8], row_bytes: usize) -> bool {
+        let pixmaps: Vec<_> = self
+            .planes
+            .iter()
+            .map(|plane| Pixmap::new(&plane.info, &plane.pixels, plane.row_bytes))
+            .collect();
+        let pixmaps: Vec<*const SkPixmap> = pixmaps.iter().map(|p| p.native() as _).collect();
+        unsafe {
+            sb::C_SkYUVAPixmaps_readPixels(
+                self.yuv_color_space,
+                pixmaps.as_ptr(),
+                self.yuva_indices.native().as_ptr(),
+                self.info.color_space().into_ptr_or_null(),
+                info.native(),
+                pixels.as_mut_ptr() as _,
+                row_bytes,
+            )
+        }
+    }
+
+    fn on_query_yuva8(
+        &self,
+    ) -> Option<(
+        YUVASizeInfo,
+        [YUVAIndex; YUVAIndex::INDEX_COUNT],
+        YUVColorSpace,
+    )> {
+        if !self.planes.iter().all(Plane::is_yuva8) {
+            return None;
+        }
+        let mut size_info = YUVASizeInfo::default();
+        for (i, plane) in self.planes.iter().enumerate() {
+            size_info.sizes[i] = plane.info.dimensions();
+            size_info.width_bytes[i] = plane.width_bytes();
+        }
+        Some((size_info, self.yuva_indices, self.yuv_color_space))
+    }
+
+    fn on_get_yuva8_planes(
+        &mut self,
+        size_info: &YUVASizeInfo,
+        _yuva_indices: &[YUVAIndex; YUVAIndex::INDEX_COUNT],
+        planes: &mut [&mut [u8]],
+    ) -> bool {
+        for (i, (plane, dst)) in self.planes.iter().zip(planes.iter_mut()).enumerate() {
+            let width_bytes = plane.width_bytes();
+            let dst_row_bytes = size_info.width_bytes[i];
+            if dst_row_bytes < width_bytes {
+                return false;
+            }
+            let rows = plane.pixels.chunks(plane.row_bytes);
+            for (src, dst) in rows.zip(dst.chunks_mut(dst_row_bytes)) {
+                dst[..width_bytes].copy_from_slice(&src[..width_bytes]);
+            }
+        }
+        true
+    }
+}
@@ -54,7 +54,71 @@ impl YUVAIndex {
             .if_true_then_some(|| num_planes.try_into().unwrap())
     }
 
+    /// The plane index and the channel of the plane, or `None` if the index is unused.
+    pub fn index(self) -> Option<(usize, ColorChannel)> {
+        self.is_valid()
+            .if_true_then_some(|| (self.index as usize, self.channel))
+    }
+
     pub(crate) fn is_valid(self) -> bool {
         self.index >= 0 && self.index < Self::INDEX_COUNT as i32
     }
 }
+
+/// Common arrangements of YUV(A) planes.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PlaneLayout {
+    /// Three planes, Y, U and V, each in the first channel of its plane.
+    I420,
+    /// Two planes, Y in the first channel of the first plane and U and V in the first and second
+    /// channel of the second plane.
+    NV12,
+    /// Four planes, Y, U, V and A, each in the first channel of its plane.
+    YUVA,
+}
+
+impl PlaneLayout {
+    /// The number of planes.
+    pub fn plane_count(self) -> usize {
+        match self {
+            PlaneLayout::I420 => 3,
+            PlaneLayout::NV12 => 2,
+            PlaneLayout::YUVA => 4,
+        }
+    }
+
+    /// The indices, in the order Y, U, V, A.
+    pub fn indices(self) -> [YUVAIndex; YUVAIndex::INDEX_COUNT] {
+        let index = |plane, channel| YUVAIndex::new(Some((plane, channel)));
+        match self {
+            PlaneLayout::I420 => [
+                index(0, ColorChannel::R),
+                index(1, ColorChannel::R),
+                index(2, ColorChannel::R),
+                YUVAIndex::new(None),
+            ],
+            PlaneLayout::NV12 => [
+                index(0, ColorChannel::R),
+                index(1, ColorChannel::R),
+                index(1, ColorChannel::G),
+                YUVAIndex::new(None),
+            ],
+            PlaneLayout::YUVA => [
+                index(0, ColorChannel::R),
+                index(1, ColorChannel::R),
+                index(2, ColorChannel::R),
+                index(3, ColorChannel::R),
+            ],
+        }
+    }
+}
+
+#[test]
+fn plane_layout_indices_are_valid() {
+    for layout in &[PlaneLayout::I420, PlaneLayout::NV12, PlaneLayout::YUVA] {
+        assert_eq!(
+            YUVAIndex::are_valid_indices(&layout.indices()),
+            Some(layout.plane_count())
+        );
+    }
+}
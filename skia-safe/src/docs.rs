@@ -1,2 +1,4 @@
+mod multi_picture_document;
+pub use multi_picture_document::*;
 mod pdf_document;
 pub use pdf_document::*;
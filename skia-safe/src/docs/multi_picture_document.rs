@@ -0,0 +1,103 @@
+/// Multi picture documents (`.mskp`), which store every page as a [`Picture`](crate::Picture).
+pub mod mskp {
+    use crate::interop::{RustStream, RustWStream};
+    use crate::prelude::*;
+    use crate::{DeserialProcs, Document, Picture, Size, WriterDocument};
+    use skia_bindings as sb;
+    use skia_bindings::{SkDocument, SkPicture, SkSize, SkWStream};
+    use std::ffi::c_void;
+    use std::io;
+
+    /// A page that was read from a multi picture document.
+    #[derive(Clone)]
+    pub struct Page {
+        pub picture: Picture,
+        pub size: Size,
+    }
+
+    /// Creates a multi picture document that records the pages that are added. The encoded
+    /// document is returned by [`Document::close()`].
+    pub fn new_document() -> Document {
+        Document::new_in_memory(make_document)
+    }
+
+    /// Creates a multi picture document that records the pages that are added and writes them
+    /// to `output` when the document is closed.
+    pub fn new_writer_document<'a>(output: impl io::Write + 'a) -> WriterDocument<'a> {
+        let mut stream = RustWStream::new(output);
+        let document = make_document(stream.native_mut());
+        Document::new(stream, document)
+    }
+
+    fn make_document(stream: &mut SkWStream) -> RCHandle<SkDocument> {
+        RCHandle::from_ptr(unsafe { sb::C_SkMultiPictureDocument_Make(stream) }).unwrap()
+    }
+
+    /// Reads all pages of a multi picture document from `stream`.
+    pub fn read(stream: impl io::Read + io::Seek) -> io::Result<Vec<Page>> {
+        read_with_procs(stream, &mut DeserialProcs::default())
+    }
+
+    /// Reads all pages of a multi picture document from `stream` and uses `procs` to
+    /// deserialize the pictures, images and typefaces the pages contain.
+    pub fn read_with_procs(
+        stream: impl io::Read + io::Seek,
+        procs: &mut DeserialProcs,
+    ) -> io::Result<Vec<Page>> {
+        let mut stream = RustStream::new(stream)?;
+        let mut pages: Vec<Page> = Vec::new();
+        let valid = unsafe {
+            sb::C_SkMultiPictureDocument_Read(
+                stream.native_mut(),
+                &procs.native(),
+                Some(add_page),
+                &mut pages as *mut Vec<Page> as *mut c_void,
+            )
+        };
+        resume_callback_panic();
+        let valid = stream.error().clone().into_result(valid)?;
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid multi picture document",
+            ));
+        }
+        Ok(pages)
+    }
+
+    unsafe extern "C" fn add_page(
+        pages: *mut c_void,
+        picture: *mut SkPicture,
+        size: *const SkSize,
+    ) {
+        let pages = &mut *(pages as *mut Vec<Page>);
+        pages.push(Page {
+            picture: Picture::from_ptr(picture).unwrap(),
+            size: *Size::from_native_ref(&*size),
+        })
+    }
+
+    #[test]
+    fn pages_are_read_back_with_their_sizes() {
+        use crate::{Color, Paint, Rect};
+
+        let mut mskp = Vec::new();
+        {
+            let mut document = new_writer_document(&mut mskp).begin_page((100, 50), None);
+            document
+                .canvas()
+                .draw_rect(Rect::from_wh(10.0, 10.0), &Paint::default());
+            let mut document = document.end_page().begin_page((30, 40), None);
+            document.canvas().clear(Color::RED);
+            document.end_page().close().unwrap();
+        }
+
+        let pages = read(io::Cursor::new(&mskp)).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].size, Size::new(100.0, 50.0));
+        assert_eq!(pages[1].size, Size::new(30.0, 40.0));
+        assert!(pages[0].picture.approximate_op_count() > 0);
+
+        assert!(read(io::Cursor::new(b"not a multi picture document")).is_err());
+    }
+}
@@ -1,12 +1,67 @@
 pub mod pdf {
-    use crate::interop::DynamicMemoryWStream;
+    use crate::interop::RustWStream;
     use crate::prelude::*;
-    use crate::{scalar, DateTime, Document};
+    use crate::{scalar, Canvas, DateTime, Document, WriterDocument};
     use skia_bindings as sb;
-    use skia_bindings::{SkPDF_Metadata, SkString};
+    use skia_bindings::{
+        SkDocument, SkPDF_Metadata, SkPDF_StructureElementNode, SkString, SkWStream,
+    };
+    use std::{io, ptr};
 
-    // TODO: DocumentStructureType
-    // TODO: StructureElementNode
+    pub use skia_bindings::SkPDF_DocumentStructureType as DocumentStructureType;
+    #[test]
+    fn test_document_structure_type_naming() {
+        let _ = DocumentStructureType::BibEntry;
+    }
+
+    /// A node in the structure element tree of a tagged PDF.
+    ///
+    /// The content that is drawn after [`set_node_id()`] was called with the `node_id` of a node
+    /// is associated with that node.
+    #[derive(Clone, Debug)]
+    pub struct StructureElementNode {
+        pub structure_type: DocumentStructureType,
+        /// The id of the node, must be unique in the tree.
+        pub node_id: i32,
+        pub children: Vec<StructureElementNode>,
+        /// The alternate description of the content, for example of a figure.
+        pub alt: String,
+        /// The language of the content, for example `"en-US"`.
+        pub lang: String,
+    }
+
+    impl Default for StructureElementNode {
+        fn default() -> Self {
+            Self::new(DocumentStructureType::NonStruct, 0)
+        }
+    }
+
+    impl StructureElementNode {
+        pub fn new(structure_type: DocumentStructureType, node_id: i32) -> Self {
+            Self {
+                structure_type,
+                node_id,
+                children: Vec::new(),
+                alt: String::new(),
+                lang: String::new(),
+            }
+        }
+
+        pub fn with_children(mut self, children: impl IntoIterator<Item = Self>) -> Self {
+            self.children.extend(children);
+            self
+        }
+
+        pub fn with_alt(mut self, alt: impl Into<String>) -> Self {
+            self.alt = alt.into();
+            self
+        }
+
+        pub fn with_lang(mut self, lang: impl Into<String>) -> Self {
+            self.lang = lang.into();
+            self
+        }
+    }
 
     #[derive(Clone, Debug, Default)]
     pub struct Metadata {
@@ -21,13 +76,38 @@ pub mod pdf {
         pub raster_dpi: Option<scalar>,
         pub pdfa: bool,
         pub encoding_quality: Option<i32>,
-        // TODO: fStructureElementTreeRoot
+        /// The root of the structure element tree, if set, the document is a tagged PDF.
+        pub structure_element_tree_root: Option<StructureElementNode>,
     }
 
-    // TODO: SetNodeId
+    /// Associates the content that is drawn on `canvas` with the [`StructureElementNode`] of
+    /// `node_id`, until the next call.
+    ///
+    /// `canvas` must be the canvas of a page of a PDF document, otherwise this has no effect.
+    pub fn set_node_id(canvas: &mut Canvas, node_id: i32) {
+        unsafe { sb::C_SkPDF_SetNodeId(canvas.native_mut(), node_id) }
+    }
 
+    /// Creates a PDF document that is written to memory. The encoded document is returned by
+    /// [`Document::close()`].
     pub fn new_document(metadata: Option<&Metadata>) -> Document {
+        Document::new_in_memory(|stream| make_document(stream, metadata))
+    }
+
+    /// Creates a PDF document that is written to `output` while pages are added.
+    pub fn new_writer_document<'a>(
+        output: impl io::Write + 'a,
+        metadata: Option<&Metadata>,
+    ) -> WriterDocument<'a> {
+        let mut stream = RustWStream::new(output);
+        let document = make_document(stream.native_mut(), metadata);
+        Document::new(stream, document)
+    }
+
+    fn make_document(stream: &mut SkWStream, metadata: Option<&Metadata>) -> RCHandle<SkDocument> {
         let mut md = InternalMetadata::default();
+        // the tree is copied by the document, so it must only live until the document is made.
+        let mut structure_element_tree = None;
         if let Some(metadata) = metadata {
             let internal = md.native_mut();
             internal.fTitle.set_s(&metadata.title);
@@ -49,17 +129,61 @@ pub mod pdf {
             if let Some(encoding_quality) = metadata.encoding_quality {
                 internal.fEncodingQuality = encoding_quality
             }
+            if let Some(root) = &metadata.structure_element_tree_root {
+                let tree = NativeStructureElementTree::new(root);
+                unsafe { sb::C_SkPDF_Metadata_setStructureElementTreeRoot(internal, tree.0) }
+                structure_element_tree = Some(tree);
+            }
         }
 
-        // we can't move the memory stream around anymore as soon it's referred by
-        // the document.
-        let mut memory_stream = Box::pin(DynamicMemoryWStream::new());
-        let document = RCHandle::from_ptr(unsafe {
-            sb::C_SkPDF_MakeDocument(memory_stream.native_mut().base_mut(), md.native())
-        })
-        .unwrap();
+        let document =
+            RCHandle::from_ptr(unsafe { sb::C_SkPDF_MakeDocument(stream, md.native()) }).unwrap();
+        drop(structure_element_tree);
+        document
+    }
+
+    //
+    // Helper for building the native structure element tree.
+    //
+
+    struct NativeStructureElementTree(*mut SkPDF_StructureElementNode);
+
+    impl NativeStructureElementTree {
+        fn new(root: &StructureElementNode) -> Self {
+            unsafe {
+                let native = sb::C_SkPDF_StructureElementNode_NewArray(1);
+                Self::set(native, root);
+                Self(native)
+            }
+        }
+
+        unsafe fn set(native: *mut SkPDF_StructureElementNode, node: &StructureElementNode) {
+            let children = if node.children.is_empty() {
+                ptr::null_mut()
+            } else {
+                sb::C_SkPDF_StructureElementNode_NewArray(node.children.len())
+            };
+            for (i, child) in node.children.iter().enumerate() {
+                Self::set(children.add(i), child);
+            }
+            sb::C_SkPDF_StructureElementNode_set(
+                native,
+                node.structure_type,
+                node.node_id,
+                node.alt.as_ptr() as _,
+                node.alt.len(),
+                node.lang.as_ptr() as _,
+                node.lang.len(),
+                children,
+                node.children.len(),
+            )
+        }
+    }
 
-        Document::new(memory_stream, document)
+    impl Drop for NativeStructureElementTree {
+        fn drop(&mut self) {
+            unsafe { sb::C_SkPDF_StructureElementNode_DeleteArray(self.0, 1) }
+        }
     }
 
     //
@@ -89,4 +213,44 @@ pub mod pdf {
             unsafe { self.set1(bytes.as_ptr() as _, bytes.len()) }
         }
     }
+
+    #[test]
+    fn tagged_pdf_contains_structure_tree() {
+        use crate::{Paint, Rect};
+
+        let metadata = Metadata {
+            structure_element_tree_root: Some(
+                StructureElementNode::new(DocumentStructureType::Document, 1)
+                    .with_lang("en-US")
+                    .with_children(vec![
+                        StructureElementNode::new(DocumentStructureType::H1, 2),
+                        StructureElementNode::new(DocumentStructureType::Figure, 3)
+                            .with_alt("Company logo"),
+                    ]),
+            ),
+            ..Default::default()
+        };
+
+        let mut document = new_document(Some(&metadata)).begin_page((100, 100), None);
+        let canvas = document.canvas();
+        set_node_id(canvas, 2);
+        canvas.draw_rect(Rect::from_wh(50.0, 10.0), &Paint::default());
+        set_node_id(canvas, 3);
+        canvas.draw_rect(Rect::from_xywh(0.0, 20.0, 50.0, 50.0), &Paint::default());
+        let data = document.end_page().close();
+
+        let pdf = String::from_utf8_lossy(data.as_bytes());
+        assert!(pdf.contains("/StructTreeRoot"));
+        assert!(pdf.contains("Company logo"));
+    }
+
+    #[test]
+    fn writer_document_writes_to_output() {
+        let mut pdf = Vec::new();
+        {
+            let document = new_writer_document(&mut pdf, None).begin_page((100, 100), None);
+            document.end_page().close().unwrap();
+        }
+        assert!(pdf.starts_with(b"%PDF"));
+    }
 }
@@ -0,0 +1,68 @@
+//! Image encoding.
+//!
+//! The functions at the top level select the encoder by [`EncodedImageFormat`] and take a single
+//! quality value. The format specific modules [`png`], [`jpeg`] and [`webp`] expose all options
+//! of the individual encoders.
+
+pub mod jpeg;
+pub mod png;
+pub mod webp;
+
+use crate::interop::RustWStream;
+use crate::prelude::*;
+use crate::{Bitmap, Data, EncodedImageFormat, Pixmap};
+use skia_bindings as sb;
+use std::io;
+
+pub fn pixmap(src: &Pixmap, format: EncodedImageFormat, quality: usize) -> Option<Data> {
+    Data::from_ptr(unsafe {
+        sb::C_SkEncodePixmap(src.native(), format, quality.try_into().unwrap())
+    })
+}
+
+pub fn bitmap(src: &Bitmap, format: EncodedImageFormat, quality: usize) -> Option<Data> {
+    Data::from_ptr(unsafe {
+        sb::C_SkEncodeBitmap(src.native(), format, quality.try_into().unwrap())
+    })
+}
+
+/// Encodes `src` and writes the encoded image to `writer`.
+pub fn pixmap_to_writer(
+    writer: impl io::Write,
+    src: &Pixmap,
+    format: EncodedImageFormat,
+    quality: usize,
+) -> io::Result<()> {
+    with_writer_stream(writer, |stream| unsafe {
+        sb::C_SkEncodeImage(stream, src.native(), format, quality.try_into().unwrap())
+    })
+}
+
+/// Runs `encode` with a stream that writes to `writer`.
+///
+/// Returns the first I/O error, or [`io::ErrorKind::InvalidInput`] if the encoder failed for
+/// other reasons, like an unsupported color type.
+pub(crate) fn with_writer_stream(
+    writer: impl io::Write,
+    encode: impl FnOnce(*mut skia_bindings::SkWStream) -> bool,
+) -> io::Result<()> {
+    let mut stream = RustWStream::new(writer);
+    let encoded = encode(stream.native_mut());
+    stream.flush()?;
+    if encoded {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "failed to encode the image",
+        ))
+    }
+}
+
+/// Runs `encode` with a stream that writes to memory and returns the encoded data.
+pub(crate) fn with_memory_stream(
+    encode: impl FnOnce(*mut skia_bindings::SkWStream) -> bool,
+) -> Option<Data> {
+    let mut stream = crate::interop::DynamicMemoryWStream::new();
+    encode(stream.native_mut().base_mut()).if_true_then_some(|| stream.detach_as_data())
+}
@@ -0,0 +1,95 @@
+//! JPEG encoding, see `SkJpegEncoder`.
+
+use crate::prelude::*;
+use crate::{Data, Pixmap};
+use skia_bindings as sb;
+use skia_bindings::SkJpegEncoder_Downsample;
+use std::io;
+
+/// Chroma subsampling.
+#[repr(i32)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Downsample {
+    /// Reduces the chroma resolution in both directions (4:2:0).
+    BothDirections = SkJpegEncoder_Downsample::k420 as _,
+    /// Reduces the chroma resolution horizontally only (4:2:2).
+    Horizontal = SkJpegEncoder_Downsample::k422 as _,
+    /// Keeps the full chroma resolution (4:4:4).
+    No = SkJpegEncoder_Downsample::k444 as _,
+}
+
+impl NativeTransmutable<SkJpegEncoder_Downsample> for Downsample {}
+#[test]
+fn test_downsample_layout() {
+    Downsample::test_layout()
+}
+
+impl Default for Downsample {
+    fn default() -> Self {
+        Downsample::BothDirections
+    }
+}
+
+/// How transparent pixels are encoded, JPEG does not support transparency.
+pub use skia_bindings::SkJpegEncoder_AlphaOption as AlphaOption;
+#[test]
+fn test_alpha_option_naming() {
+    let _ = AlphaOption::BlendOnBlack;
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Options {
+    /// The quality, from `0` to `100`.
+    pub quality: u32,
+    pub downsample: Downsample,
+    pub alpha_option: AlphaOption,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            quality: 100,
+            downsample: Downsample::default(),
+            alpha_option: AlphaOption::Ignore,
+        }
+    }
+}
+
+/// Encodes `src` as JPEG and writes it to `writer`.
+pub fn encode(writer: impl io::Write, src: &Pixmap, options: &Options) -> io::Result<()> {
+    crate::encode::with_writer_stream(writer, |stream| encode_to_stream(stream, src, options))
+}
+
+/// Encodes `src` as JPEG.
+pub fn encode_to_data(src: &Pixmap, options: &Options) -> Option<Data> {
+    crate::encode::with_memory_stream(|stream| encode_to_stream(stream, src, options))
+}
+
+fn encode_to_stream(stream: *mut sb::SkWStream, src: &Pixmap, options: &Options) -> bool {
+    unsafe {
+        sb::C_SkJpegEncoder_Encode(
+            stream,
+            src.native(),
+            options.quality.min(100) as _,
+            options.downsample.into_native(),
+            options.alpha_option,
+        )
+    }
+}
+
+#[test]
+fn encode_writes_jpeg() {
+    let mut bitmap = crate::Bitmap::new();
+    bitmap.alloc_n32_pixels((2, 2), None);
+    bitmap.erase_color(crate::Color::RED);
+    for &quality in &[0, 50, 101] {
+        let options = Options {
+            quality,
+            ..Options::default()
+        };
+        let mut jpeg = Vec::new();
+        encode(&mut jpeg, bitmap.pixmap(), &options).unwrap();
+        assert_eq!(&jpeg[0..3], &[0xff, 0xd8, 0xff]);
+        assert_eq!(&jpeg[jpeg.len() - 2..], &[0xff, 0xd9]);
+    }
+}
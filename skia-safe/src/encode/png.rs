@@ -0,0 +1,156 @@
+//! PNG encoding, see `SkPngEncoder`.
+
+use crate::prelude::*;
+use crate::{Data, Pixmap};
+use skia_bindings as sb;
+use std::ffi::CString;
+use std::io;
+use std::os::raw::c_char;
+
+bitflags! {
+    /// The row filters the encoder may choose from.
+    pub struct FilterFlag: u32 {
+        const ZERO = 0x00;
+        const NONE = 0x08;
+        const SUB = 0x10;
+        const UP = 0x20;
+        const AVG = 0x40;
+        const PAETH = 0x80;
+        const ALL = 0xf8;
+    }
+}
+
+impl Default for FilterFlag {
+    fn default() -> Self {
+        FilterFlag::ALL
+    }
+}
+
+/// A `tEXt` chunk.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Comment {
+    pub keyword: String,
+    pub text: String,
+}
+
+impl Comment {
+    pub fn new(keyword: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            keyword: keyword.into(),
+            text: text.into(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Options {
+    /// Selects which row filters the encoder may use. Fewer filters encode faster, but may
+    /// result in larger files.
+    pub filter_flags: FilterFlag,
+    /// The zlib compression level, from `0` (no compression) to `9` (best compression).
+    /// Values outside of this range are clamped.
+    pub z_lib_level: i32,
+    /// Text chunks to embed.
+    pub comments: Vec<Comment>,
+    /// Whether the color space of the source is written to the file (as an `iCCP` or `sRGB`
+    /// chunk).
+    pub embed_icc_profile: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            filter_flags: FilterFlag::default(),
+            z_lib_level: 6,
+            comments: Vec::new(),
+            embed_icc_profile: true,
+        }
+    }
+}
+
+/// Encodes `src` as PNG and writes it to `writer`.
+pub fn encode(writer: impl io::Write, src: &Pixmap, options: &Options) -> io::Result<()> {
+    let comments = comment_strings(options)?;
+    let src = source(src, options)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the pixmap has no pixels"))?;
+    crate::encode::with_writer_stream(writer, |stream| {
+        encode_to_stream(stream, &src, options, &comments)
+    })
+}
+
+/// Encodes `src` as PNG.
+pub fn encode_to_data(src: &Pixmap, options: &Options) -> Option<Data> {
+    let comments = comment_strings(options).ok()?;
+    let src = source(src, options)?;
+    crate::encode::with_memory_stream(|stream| encode_to_stream(stream, &src, options, &comments))
+}
+
+fn encode_to_stream(
+    stream: *mut sb::SkWStream,
+    src: &Pixmap,
+    options: &Options,
+    comments: &[CString],
+) -> bool {
+    let comments: Vec<*const c_char> = comments.iter().map(|c| c.as_ptr()).collect();
+    unsafe {
+        sb::C_SkPngEncoder_Encode(
+            stream,
+            src.native(),
+            options.filter_flags.bits() as _,
+            options.z_lib_level.max(0).min(9),
+            comments.as_ptr(),
+            comments.len(),
+        )
+    }
+}
+
+/// Returns the keyword / text pairs as a flat list of C strings.
+fn comment_strings(options: &Options) -> io::Result<Vec<CString>> {
+    options
+        .comments
+        .iter()
+        .flat_map(|c| vec![&c.keyword, &c.text])
+        .map(|s| {
+            CString::new(s.as_str()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+        })
+        .collect()
+}
+
+/// Returns a pixmap that refers to the pixels of `src`, without a color space if the
+/// profile should not be embedded.
+fn source(src: &Pixmap, options: &Options) -> Option<Pixmap> {
+    let mut pixmap = src.extract_subset(src.bounds())?;
+    if !options.embed_icc_profile {
+        pixmap.set_color_space(None);
+    }
+    Some(pixmap)
+}
+
+#[test]
+fn encode_writes_png_with_comments() {
+    let mut bitmap = crate::Bitmap::new();
+    bitmap.alloc_n32_pixels((2, 2), None);
+    bitmap.erase_color(crate::Color::RED);
+    let options = Options {
+        comments: vec![Comment::new("Title", "red")],
+        ..Options::default()
+    };
+    let mut png = Vec::new();
+    encode(&mut png, bitmap.pixmap(), &options).unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+    assert!(png.windows(9).any(|w| w == b"Title\0red"));
+}
+
+#[test]
+fn encode_clamps_z_lib_level() {
+    let mut bitmap = crate::Bitmap::new();
+    bitmap.alloc_n32_pixels((2, 2), None);
+    bitmap.erase_color(crate::Color::RED);
+    for &z_lib_level in &[-1, 10] {
+        let options = Options {
+            z_lib_level,
+            ..Options::default()
+        };
+        assert!(encode_to_data(bitmap.pixmap(), &options).is_some());
+    }
+}
@@ -0,0 +1,71 @@
+//! WebP encoding, see `SkWebpEncoder`.
+
+use crate::prelude::*;
+use crate::{Data, Pixmap};
+use skia_bindings as sb;
+use std::io;
+
+pub use skia_bindings::SkWebpEncoder_Compression as Compression;
+#[test]
+fn test_compression_naming() {
+    let _ = Compression::Lossless;
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Options {
+    pub compression: Compression,
+    /// For [`Compression::Lossy`], the visual quality from `0.0` to `100.0`.
+    /// For [`Compression::Lossless`], the effort spent on compression, from `0.0` (fastest) to
+    /// `100.0` (smallest).
+    /// Values outside of this range are clamped.
+    pub quality: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            compression: Compression::Lossy,
+            quality: 100.0,
+        }
+    }
+}
+
+/// Encodes `src` as WebP and writes it to `writer`.
+pub fn encode(writer: impl io::Write, src: &Pixmap, options: &Options) -> io::Result<()> {
+    crate::encode::with_writer_stream(writer, |stream| encode_to_stream(stream, src, options))
+}
+
+/// Encodes `src` as WebP.
+pub fn encode_to_data(src: &Pixmap, options: &Options) -> Option<Data> {
+    crate::encode::with_memory_stream(|stream| encode_to_stream(stream, src, options))
+}
+
+fn encode_to_stream(stream: *mut sb::SkWStream, src: &Pixmap, options: &Options) -> bool {
+    unsafe {
+        sb::C_SkWebpEncoder_Encode(
+            stream,
+            src.native(),
+            options.compression,
+            options.quality.max(0.0).min(100.0),
+        )
+    }
+}
+
+#[test]
+fn encode_writes_webp() {
+    let mut bitmap = crate::Bitmap::new();
+    bitmap.alloc_n32_pixels((2, 2), None);
+    bitmap.erase_color(crate::Color::RED);
+    for &compression in &[Compression::Lossy, Compression::Lossless] {
+        for &quality in &[-1.0, 50.0, 101.0] {
+            let options = Options {
+                compression,
+                quality,
+            };
+            let mut webp = Vec::new();
+            encode(&mut webp, bitmap.pixmap(), &options).unwrap();
+            assert_eq!(&webp[0..4], b"RIFF");
+            assert_eq!(&webp[8..12], b"WEBP");
+        }
+    }
+}
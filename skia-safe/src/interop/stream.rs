@@ -7,8 +7,12 @@ use crate::prelude::*;
 use crate::Data;
 use skia_bindings as sb;
 use skia_bindings::{SkDynamicMemoryWStream, SkMemoryStream, SkStream, SkStreamAsset, SkWStream};
+use std::any::Any;
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
-use std::ptr;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::{ffi, ptr, slice};
 
 /// Trait representing an Skia allocated Stream type with a base class of SkStream.
 #[repr(transparent)]
@@ -132,6 +136,295 @@ impl Handle<SkDynamicMemoryWStream> {
     }
 }
 
+/// The first I/O error that occurred in a Rust stream adapter.
+///
+/// A panic of the underlying reader or writer is recorded as an error of the kind
+/// [`io::ErrorKind::Other`], because it can't unwind through Skia.
+///
+/// Shared between the adapter and the caller, so that the error can be retrieved even after
+/// Skia took ownership of the stream.
+#[derive(Clone, Default)]
+pub struct StreamError(Arc<Mutex<Option<io::Error>>>);
+
+impl StreamError {
+    fn set(&self, error: io::Error) {
+        let mut slot = self.0.lock().unwrap();
+        if slot.is_none() {
+            *slot = Some(error)
+        }
+    }
+
+    fn is_set(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+
+    /// Calls `f`, records a panic as an error and returns `default` instead.
+    fn catch_panic<T>(&self, default: T, f: impl FnOnce() -> T) -> T {
+        panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+            self.set(io::Error::new(
+                io::ErrorKind::Other,
+                panic_message(&*payload),
+            ));
+            default
+        })
+    }
+
+    /// Returns the first error that occurred, if any.
+    pub fn take(&self) -> Option<io::Error> {
+        self.0.lock().unwrap().take()
+    }
+
+    /// Returns the first error that occurred, or `value` if there was none.
+    pub fn into_result<T>(self, value: T) -> io::Result<T> {
+        match self.take() {
+            Some(error) => Err(error),
+            None => Ok(value),
+        }
+    }
+}
+
+struct ReadState<R> {
+    reader: R,
+    start: u64,
+    error: StreamError,
+}
+
+/// An `SkStreamAsset` that reads from a Rust `Read + Seek` implementation.
+///
+/// The stream starts at the current position of the reader. I/O errors end the stream and are
+/// recorded in its [`StreamError`].
+pub struct RustStream<'a> {
+    native: *mut SkStreamAsset,
+    error: StreamError,
+    pd: PhantomData<&'a mut ()>,
+}
+
+impl Drop for RustStream<'_> {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkStream_delete(self.native as _) }
+    }
+}
+
+impl NativeAccess<SkStreamAsset> for RustStream<'_> {
+    fn native(&self) -> &SkStreamAsset {
+        unsafe { &*self.native }
+    }
+    fn native_mut(&mut self) -> &mut SkStreamAsset {
+        unsafe { &mut *self.native }
+    }
+}
+
+impl<'a> RustStream<'a> {
+    pub fn new<R: Read + Seek + 'a>(mut reader: R) -> io::Result<Self> {
+        let start = reader.seek(SeekFrom::Current(0))?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+        let length = end
+            .saturating_sub(start)
+            .try_into()
+            .unwrap_or(usize::max_value());
+
+        let error = StreamError::default();
+        let state = Box::new(ReadState {
+            reader,
+            start,
+            error: error.clone(),
+        });
+
+        let native = unsafe {
+            sb::C_RustStreamAsset_new(
+                Box::into_raw(state) as _,
+                length,
+                Some(read_trampoline::<R>),
+                Some(seek_trampoline::<R>),
+                Some(drop_trampoline::<ReadState<R>>),
+            )
+        };
+
+        Ok(RustStream {
+            native,
+            error,
+            pd: PhantomData,
+        })
+    }
+
+    /// The error handle of this stream.
+    pub fn error(&self) -> &StreamError {
+        &self.error
+    }
+
+    pub fn as_stream_mut(&mut self) -> &mut SkStream {
+        self.native_mut().base_mut()
+    }
+}
+
+impl RustStream<'static> {
+    /// Releases the native stream, so that it can be passed to a Skia function that takes
+    /// ownership of it.
+    pub fn into_native(self) -> (*mut SkStreamAsset, StreamError) {
+        let native = self.native;
+        let error = self.error.clone();
+        std::mem::forget(self);
+        (native, error)
+    }
+}
+
+struct WriteState<W> {
+    writer: W,
+    error: StreamError,
+}
+
+/// An `SkWStream` that writes to a Rust `Write` implementation.
+///
+/// After an I/O error, all further writes fail. The error is recorded in the stream's
+/// [`StreamError`].
+pub struct RustWStream<'a> {
+    native: *mut SkWStream,
+    error: StreamError,
+    pd: PhantomData<&'a mut ()>,
+}
+
+impl Drop for RustWStream<'_> {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkWStream_delete(self.native) }
+    }
+}
+
+impl NativeAccess<SkWStream> for RustWStream<'_> {
+    fn native(&self) -> &SkWStream {
+        unsafe { &*self.native }
+    }
+    fn native_mut(&mut self) -> &mut SkWStream {
+        unsafe { &mut *self.native }
+    }
+}
+
+impl<'a> RustWStream<'a> {
+    pub fn new<W: Write + 'a>(writer: W) -> Self {
+        let error = StreamError::default();
+        let state = Box::new(WriteState {
+            writer,
+            error: error.clone(),
+        });
+
+        let native = unsafe {
+            sb::C_RustWStream_new(
+                Box::into_raw(state) as _,
+                Some(write_trampoline::<W>),
+                Some(flush_trampoline::<W>),
+                Some(drop_trampoline::<WriteState<W>>),
+            )
+        };
+
+        RustWStream {
+            native,
+            error,
+            pd: PhantomData,
+        }
+    }
+
+    /// The error handle of this stream.
+    pub fn error(&self) -> &StreamError {
+        &self.error
+    }
+
+    /// Flushes the underlying writer and returns the first error that occurred.
+    pub fn flush(&mut self) -> io::Result<()> {
+        unsafe { sb::C_SkWStream_flush(self.native) }
+        self.error.clone().into_result(())
+    }
+}
+
+// The trampolines are called from Skia, so panics of the reader or writer are caught and
+// recorded in the StreamError.
+
+unsafe extern "C" fn read_trampoline<R: Read>(
+    state: *mut ffi::c_void,
+    buffer: *mut ffi::c_void,
+    size: usize,
+) -> usize {
+    let ReadState { reader, error, .. } = &mut *(state as *mut ReadState<R>);
+    let buffer = slice::from_raw_parts_mut(buffer as *mut u8, size);
+    error.catch_panic(0, || {
+        let mut read = 0;
+        while read < size {
+            match reader.read(&mut buffer[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    error.set(e);
+                    break;
+                }
+            }
+        }
+        read
+    })
+}
+
+unsafe extern "C" fn seek_trampoline<R: Seek>(state: *mut ffi::c_void, position: usize) -> bool {
+    let ReadState {
+        reader,
+        start,
+        error,
+    } = &mut *(state as *mut ReadState<R>);
+    error.catch_panic(false, || {
+        match reader.seek(SeekFrom::Start(*start + position as u64)) {
+            Ok(_) => true,
+            Err(e) => {
+                error.set(e);
+                false
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn write_trampoline<W: Write>(
+    state: *mut ffi::c_void,
+    buffer: *const ffi::c_void,
+    size: usize,
+) -> bool {
+    let WriteState { writer, error } = &mut *(state as *mut WriteState<W>);
+    if error.is_set() {
+        return false;
+    }
+    let buffer = slice::from_raw_parts(buffer as *const u8, size);
+    error.catch_panic(false, || match writer.write_all(buffer) {
+        Ok(()) => true,
+        Err(e) => {
+            error.set(e);
+            false
+        }
+    })
+}
+
+unsafe extern "C" fn flush_trampoline<W: Write>(state: *mut ffi::c_void) {
+    let WriteState { writer, error } = &mut *(state as *mut WriteState<W>);
+    error.catch_panic((), || {
+        if let Err(e) = writer.flush() {
+            error.set(e)
+        }
+    })
+}
+
+unsafe extern "C" fn drop_trampoline<T>(state: *mut ffi::c_void) {
+    // there is no error to record a panic in when the state is dropped.
+    if panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(state as *mut T)))).is_err() {
+        std::process::abort()
+    }
+}
+
+/// Returns the message of a panic payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).into()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic in a Rust stream".into()
+    }
+}
+
 #[test]
 fn detaching_empty_dynamic_memory_w_stream_leads_to_non_null_data() {
     let mut stream = DynamicMemoryWStream::new();
@@ -144,3 +437,48 @@ fn memory_stream_from_bytes() {
     let stream = MemoryStream::from_bytes(&[1, 2, 3]);
     drop(stream);
 }
+
+#[test]
+fn rust_stream_reads_from_current_position() {
+    let mut cursor = io::Cursor::new(vec![1u8, 2, 3, 4]);
+    cursor.set_position(1);
+    let mut stream = RustStream::new(&mut cursor).unwrap();
+    let data = Data::from_ptr(unsafe { sb::C_SkData_MakeFromStream(stream.as_stream_mut(), 3) });
+    assert_eq!(data.unwrap().as_bytes(), &[2, 3, 4]);
+    assert!(stream.error().take().is_none());
+}
+
+#[test]
+fn rust_stream_records_panics_of_the_reader() {
+    struct PanickingReader;
+
+    impl Read for PanickingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            panic!("reader panicked")
+        }
+    }
+
+    impl Seek for PanickingReader {
+        fn seek(&mut self, _pos: SeekFrom) -> io::Result<u64> {
+            Ok(0)
+        }
+    }
+
+    let mut stream = RustStream::new(PanickingReader).unwrap();
+    let data = Data::from_ptr(unsafe { sb::C_SkData_MakeFromStream(stream.as_stream_mut(), 1) });
+    assert!(data.is_none());
+    let error = stream.error().take().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::Other);
+    assert_eq!(error.to_string(), "reader panicked");
+}
+
+#[test]
+fn rust_w_stream_writes_to_writer() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = RustWStream::new(&mut buffer);
+        assert!(unsafe { sb::C_SkWStream_write(stream.native_mut(), [1u8, 2].as_ptr() as _, 2) });
+        stream.flush().unwrap();
+    }
+    assert_eq!(buffer, [1, 2]);
+}
@@ -0,0 +1,518 @@
+//! Playback of Lottie animations with Skia's skottie module.
+
+use crate::interop::{self, MemoryStream, NativeStreamBase, RustStream};
+use crate::prelude::*;
+use crate::{scalar, Canvas, Color, Data, FontMgr, Image, Rect, Size, Typeface};
+use skia_bindings as sb;
+use skia_bindings::{
+    skottie_Animation, RustPropertyObserver_Param, RustResourceProvider_Param,
+    SkottieColorPropertyHandle, SkottieOpacityPropertyHandle, SkottieTextPropertyHandle,
+};
+use std::{io, mem, ptr};
+
+/// A Lottie animation.
+pub type Animation = RCHandle<skottie_Animation>;
+
+impl NativeRefCounted for skottie_Animation {
+    fn _ref(&self) {
+        unsafe { sb::C_skottie_Animation_ref(self) }
+    }
+
+    fn _unref(&self) {
+        unsafe { sb::C_skottie_Animation_unref(self) }
+    }
+
+    fn unique(&self) -> bool {
+        unsafe { sb::C_skottie_Animation_unique(self) }
+    }
+}
+
+impl RCHandle<skottie_Animation> {
+    /// Parses an animation from Lottie JSON, returns `None` if the JSON is not a valid
+    /// animation.
+    ///
+    /// Use a [`Builder`] to load external images and fonts or to observe the properties of the
+    /// animation.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Animation> {
+        Builder::default().make(bytes)
+    }
+
+    /// Reads and parses an animation from Lottie JSON.
+    pub fn from_stream(stream: impl io::Read + io::Seek) -> io::Result<Animation> {
+        Builder::default().make_from_stream(stream)
+    }
+
+    /// The Lottie version the animation was exported with.
+    pub fn version(&self) -> String {
+        let mut version = interop::String::default();
+        unsafe { sb::C_skottie_Animation_version(self.native(), version.native_mut()) };
+        version.as_str().into()
+    }
+
+    /// The duration of the animation in seconds.
+    pub fn duration(&self) -> f64 {
+        unsafe { sb::C_skottie_Animation_duration(self.native()) }
+    }
+
+    /// The frames per second the animation was designed for.
+    pub fn fps(&self) -> f64 {
+        unsafe { sb::C_skottie_Animation_fps(self.native()) }
+    }
+
+    /// The size of the animation's coordinate space.
+    pub fn size(&self) -> Size {
+        let mut size = Size::default();
+        unsafe { sb::C_skottie_Animation_size(self.native(), size.native_mut()) };
+        size
+    }
+
+    /// Updates the animation to the normalized time `t`, in the range `0..=1`.
+    pub fn seek(&mut self, t: scalar) -> &mut Self {
+        unsafe { sb::C_skottie_Animation_seek(self.native_mut(), t) }
+        self
+    }
+
+    /// Updates the animation to the time `t` in seconds.
+    pub fn seek_frame_time(&mut self, t: f64) -> &mut Self {
+        unsafe { sb::C_skottie_Animation_seekFrameTime(self.native_mut(), t) }
+        self
+    }
+
+    /// Draws the current frame to `canvas`, scaled to fit `dst`, or at its original
+    /// [`size()`](Self::size) at the origin if `dst` is `None`.
+    pub fn render(&self, canvas: &mut Canvas, dst: Option<&Rect>) {
+        unsafe {
+            sb::C_skottie_Animation_render(
+                self.native(),
+                canvas.native_mut(),
+                dst.native_ptr_or_null(),
+            )
+        }
+    }
+}
+
+/// Loads the external images and fonts an animation refers to.
+pub trait ResourceProvider {
+    /// Loads the resource `name` in the directory `path`.
+    fn load(&mut self, path: &str, name: &str) -> Option<Data>;
+
+    /// Loads the image asset `name` in the directory `path`, `id` is the asset id in the
+    /// animation.
+    ///
+    /// The default implementation decodes the data that is returned by [`load()`](Self::load).
+    fn load_image_asset(&mut self, path: &str, name: &str, _id: &str) -> Option<Image> {
+        self.load(path, name)
+            .and_then(|data| Image::from_encoded(data, None))
+    }
+
+    /// Loads the font data of the font `name` from `url`.
+    fn load_font(&mut self, _name: &str, _url: &str) -> Option<Data> {
+        None
+    }
+}
+
+/// Observes the properties of an animation while it is built.
+///
+/// Properties are identified by the name of the node they belong to, the handles can be kept to
+/// change the property later on. Note that a change is overwritten by the next seek if the
+/// property is animated.
+pub trait PropertyObserver {
+    fn on_color_property(&mut self, _node_name: &str, _handle: ColorPropertyHandle) {}
+    fn on_opacity_property(&mut self, _node_name: &str, _handle: OpacityPropertyHandle) {}
+    fn on_text_property(&mut self, _node_name: &str, _handle: TextPropertyHandle) {}
+}
+
+/// A handle to a color property of an animation.
+pub type ColorPropertyHandle = RefHandle<SkottieColorPropertyHandle>;
+
+impl NativeDrop for SkottieColorPropertyHandle {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkottieColorPropertyHandle_delete(self) }
+    }
+}
+
+impl RefHandle<SkottieColorPropertyHandle> {
+    pub fn get(&self) -> Color {
+        Color::from_native(unsafe { sb::C_SkottieColorPropertyHandle_get(self.native()) })
+    }
+
+    pub fn set(&mut self, color: impl Into<Color>) -> &mut Self {
+        unsafe {
+            sb::C_SkottieColorPropertyHandle_set(self.native_mut(), color.into().into_native())
+        }
+        self
+    }
+}
+
+/// A handle to an opacity property of an animation, the opacity is in the range `0..=100`.
+pub type OpacityPropertyHandle = RefHandle<SkottieOpacityPropertyHandle>;
+
+impl NativeDrop for SkottieOpacityPropertyHandle {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkottieOpacityPropertyHandle_delete(self) }
+    }
+}
+
+impl RefHandle<SkottieOpacityPropertyHandle> {
+    pub fn get(&self) -> scalar {
+        unsafe { sb::C_SkottieOpacityPropertyHandle_get(self.native()) }
+    }
+
+    pub fn set(&mut self, opacity: scalar) -> &mut Self {
+        unsafe { sb::C_SkottieOpacityPropertyHandle_set(self.native_mut(), opacity) }
+        self
+    }
+}
+
+/// A handle to the text property of a text layer.
+pub type TextPropertyHandle = RefHandle<SkottieTextPropertyHandle>;
+
+impl NativeDrop for SkottieTextPropertyHandle {
+    fn drop(&mut self) {
+        unsafe { sb::C_SkottieTextPropertyHandle_delete(self) }
+    }
+}
+
+impl RefHandle<SkottieTextPropertyHandle> {
+    pub fn text(&self) -> String {
+        let mut text = interop::String::default();
+        unsafe { sb::C_SkottieTextPropertyHandle_getText(self.native(), text.native_mut()) };
+        text.as_str().into()
+    }
+
+    pub fn set_text(&mut self, text: impl AsRef<str>) -> &mut Self {
+        let text = text.as_ref();
+        unsafe {
+            sb::C_SkottieTextPropertyHandle_setText(
+                self.native_mut(),
+                text.as_ptr() as _,
+                text.len(),
+            )
+        }
+        self
+    }
+
+    pub fn text_size(&self) -> scalar {
+        unsafe { sb::C_SkottieTextPropertyHandle_getTextSize(self.native()) }
+    }
+
+    pub fn set_text_size(&mut self, text_size: scalar) -> &mut Self {
+        unsafe { sb::C_SkottieTextPropertyHandle_setTextSize(self.native_mut(), text_size) }
+        self
+    }
+
+    pub fn typeface(&self) -> Option<Typeface> {
+        Typeface::from_ptr(unsafe { sb::C_SkottieTextPropertyHandle_getTypeface(self.native()) })
+    }
+
+    pub fn set_typeface(&mut self, typeface: Typeface) -> &mut Self {
+        unsafe {
+            sb::C_SkottieTextPropertyHandle_setTypeface(self.native_mut(), typeface.into_ptr())
+        }
+        self
+    }
+}
+
+/// Builds animations with a font manager, a [`ResourceProvider`] and a [`PropertyObserver`].
+///
+/// The resource provider and the property observer are only used while an animation is built,
+/// if one of them panics, the panic is resumed after the animation is built.
+#[derive(Default)]
+pub struct Builder<'a> {
+    font_mgr: Option<FontMgr>,
+    resource_provider: Option<&'a mut dyn ResourceProvider>,
+    property_observer: Option<&'a mut dyn PropertyObserver>,
+}
+
+impl<'a> Builder<'a> {
+    /// Sets the font manager that resolves the fonts of text layers.
+    pub fn set_font_mgr(&mut self, font_mgr: impl Into<Option<FontMgr>>) -> &mut Self {
+        self.font_mgr = font_mgr.into();
+        self
+    }
+
+    pub fn set_resource_provider(
+        &mut self,
+        resource_provider: &'a mut dyn ResourceProvider,
+    ) -> &mut Self {
+        self.resource_provider = Some(resource_provider);
+        self
+    }
+
+    pub fn set_property_observer(
+        &mut self,
+        property_observer: &'a mut dyn PropertyObserver,
+    ) -> &mut Self {
+        self.property_observer = Some(property_observer);
+        self
+    }
+
+    /// Builds an animation from Lottie JSON, returns `None` if the JSON is not a valid
+    /// animation.
+    pub fn make(&mut self, bytes: &[u8]) -> Option<Animation> {
+        let mut stream = MemoryStream::from_bytes(bytes);
+        self.make_from_native_stream(stream.native_mut().as_stream_mut())
+    }
+
+    /// Builds an animation from Lottie JSON that is read from `stream`.
+    pub fn make_from_stream(&mut self, stream: impl io::Read + io::Seek) -> io::Result<Animation> {
+        let mut stream = RustStream::new(stream)?;
+        let animation = self.make_from_native_stream(stream.as_stream_mut());
+        let animation = stream.error().clone().into_result(animation)?;
+        animation.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid animation"))
+    }
+
+    fn make_from_native_stream(&mut self, stream: &mut sb::SkStream) -> Option<Animation> {
+        let resource_provider = self
+            .resource_provider
+            .as_mut()
+            .map(|rp| rust_resource_provider::new_param(&mut **rp));
+        let property_observer = self
+            .property_observer
+            .as_mut()
+            .map(|po| rust_property_observer::new_param(&mut **po));
+        let animation = Animation::from_ptr(unsafe {
+            sb::C_skottie_Animation_MakeFromStream(
+                stream,
+                self.font_mgr.clone().into_ptr_or_null(),
+                resource_provider
+                    .as_ref()
+                    .map_or(ptr::null(), |rp| rp as *const _),
+                property_observer
+                    .as_ref()
+                    .map_or(ptr::null(), |po| po as *const _),
+            )
+        });
+        resume_callback_panic();
+        animation
+    }
+}
+
+mod rust_resource_provider {
+    use super::*;
+    use skia_bindings::{SkData, SkImage, TraitObject};
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    pub fn new_param(resource_provider: &mut dyn ResourceProvider) -> RustResourceProvider_Param {
+        RustResourceProvider_Param {
+            trait_: unsafe { mem::transmute(resource_provider) },
+            load: Some(load),
+            loadImageAsset: Some(load_image_asset),
+            loadFont: Some(load_font),
+        }
+    }
+
+    extern "C" fn load(to: TraitObject, path: *const c_char, name: *const c_char) -> *mut SkData {
+        catch_callback_panic(ptr::null_mut(), || {
+            to_resource_provider(to)
+                .load(&to_str(path), &to_str(name))
+                .into_ptr_or_null()
+        })
+    }
+
+    extern "C" fn load_image_asset(
+        to: TraitObject,
+        path: *const c_char,
+        name: *const c_char,
+        id: *const c_char,
+    ) -> *mut SkImage {
+        catch_callback_panic(ptr::null_mut(), || {
+            to_resource_provider(to)
+                .load_image_asset(&to_str(path), &to_str(name), &to_str(id))
+                .into_ptr_or_null()
+        })
+    }
+
+    extern "C" fn load_font(
+        to: TraitObject,
+        name: *const c_char,
+        url: *const c_char,
+    ) -> *mut SkData {
+        catch_callback_panic(ptr::null_mut(), || {
+            to_resource_provider(to)
+                .load_font(&to_str(name), &to_str(url))
+                .into_ptr_or_null()
+        })
+    }
+
+    fn to_resource_provider<'a>(to: TraitObject) -> &'a mut dyn ResourceProvider {
+        unsafe { mem::transmute(to) }
+    }
+
+    pub(super) fn to_str<'a>(str: *const c_char) -> std::borrow::Cow<'a, str> {
+        if str.is_null() {
+            "".into()
+        } else {
+            unsafe { CStr::from_ptr(str) }.to_string_lossy()
+        }
+    }
+}
+
+mod rust_property_observer {
+    use super::rust_resource_provider::to_str;
+    use super::*;
+    use skia_bindings::TraitObject;
+    use std::os::raw::c_char;
+
+    pub fn new_param(property_observer: &mut dyn PropertyObserver) -> RustPropertyObserver_Param {
+        RustPropertyObserver_Param {
+            trait_: unsafe { mem::transmute(property_observer) },
+            onColorProperty: Some(on_color_property),
+            onOpacityProperty: Some(on_opacity_property),
+            onTextProperty: Some(on_text_property),
+        }
+    }
+
+    extern "C" fn on_color_property(
+        to: TraitObject,
+        node_name: *const c_char,
+        handle: *mut SkottieColorPropertyHandle,
+    ) {
+        catch_callback_panic((), || {
+            to_property_observer(to).on_color_property(
+                &to_str(node_name),
+                ColorPropertyHandle::from_ptr(handle).unwrap(),
+            )
+        })
+    }
+
+    extern "C" fn on_opacity_property(
+        to: TraitObject,
+        node_name: *const c_char,
+        handle: *mut SkottieOpacityPropertyHandle,
+    ) {
+        catch_callback_panic((), || {
+            to_property_observer(to).on_opacity_property(
+                &to_str(node_name),
+                OpacityPropertyHandle::from_ptr(handle).unwrap(),
+            )
+        })
+    }
+
+    extern "C" fn on_text_property(
+        to: TraitObject,
+        node_name: *const c_char,
+        handle: *mut SkottieTextPropertyHandle,
+    ) {
+        catch_callback_panic((), || {
+            to_property_observer(to).on_text_property(
+                &to_str(node_name),
+                TextPropertyHandle::from_ptr(handle).unwrap(),
+            )
+        })
+    }
+
+    fn to_property_observer<'a>(to: TraitObject) -> &'a mut dyn PropertyObserver {
+        unsafe { mem::transmute(to) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Surface;
+
+    // A 10x10 animation of one second at 30 fps, with a red rectangle that fades in. The layer
+    // ends one frame after the animation, so that it is visible at the end.
+    const BOX: &str = r#"{
+        "v": "5.5.2", "fr": 30, "ip": 0, "op": 30, "w": 10, "h": 10,
+        "layers": [{
+            "ty": 4, "nm": "Layer", "ip": 0, "op": 31, "st": 0,
+            "ks": {
+                "o": { "a": 1, "k": [
+                    { "t": 0, "s": [0], "e": [100] },
+                    { "t": 30 }
+                ] }
+            },
+            "shapes": [
+                { "ty": "rc", "nm": "Rect", "p": { "a": 0, "k": [5, 5] },
+                  "s": { "a": 0, "k": [10, 10] }, "r": { "a": 0, "k": 0 } },
+                { "ty": "fl", "nm": "Box", "c": { "a": 0, "k": [1, 0, 0, 1] },
+                  "o": { "a": 0, "k": 100 } }
+            ]
+        }]
+    }"#;
+
+    #[test]
+    fn animation_properties() {
+        let animation = Animation::from_bytes(BOX.as_bytes()).unwrap();
+        assert_eq!(animation.version(), "5.5.2");
+        assert_eq!(animation.duration(), 1.0);
+        assert_eq!(animation.fps(), 30.0);
+        assert_eq!(animation.size(), Size::new(10.0, 10.0));
+
+        assert!(Animation::from_bytes(b"{}").is_none());
+        assert!(Animation::from_stream(io::Cursor::new(BOX)).is_ok());
+    }
+
+    #[test]
+    fn render_frames() {
+        let mut animation = Animation::from_bytes(BOX.as_bytes()).unwrap();
+        let mut surface = Surface::new_raster_n32_premul((10, 10)).unwrap();
+
+        let mut render = |t: scalar| {
+            animation.seek(t);
+            surface.canvas().clear(Color::TRANSPARENT);
+            animation.render(surface.canvas(), None);
+            center_pixel(&mut surface)
+        };
+
+        assert_eq!(render(0.0).a(), 0);
+        assert_eq!(render(1.0), Color::RED);
+    }
+
+    #[test]
+    fn override_observed_color() {
+        #[derive(Default)]
+        struct Observer {
+            colors: Vec<(String, ColorPropertyHandle)>,
+        }
+
+        impl PropertyObserver for Observer {
+            fn on_color_property(&mut self, node_name: &str, handle: ColorPropertyHandle) {
+                self.colors.push((node_name.into(), handle));
+            }
+        }
+
+        let mut observer = Observer::default();
+        let mut animation = Builder::default()
+            .set_property_observer(&mut observer)
+            .make(BOX.as_bytes())
+            .unwrap();
+
+        assert_eq!(observer.colors.len(), 1);
+        let (_, color) = &mut observer.colors[0];
+        assert_eq!(color.get(), Color::RED);
+        color.set(Color::BLUE);
+        assert_eq!(color.get(), Color::BLUE);
+
+        let mut surface = Surface::new_raster_n32_premul((10, 10)).unwrap();
+        animation.seek(1.0);
+        animation.render(surface.canvas(), Some(&Rect::from_wh(10.0, 10.0)));
+        assert_eq!(center_pixel(&mut surface), Color::BLUE);
+    }
+
+    #[test]
+    #[should_panic(expected = "observer panicked")]
+    fn panics_of_callbacks_are_resumed() {
+        struct Observer;
+
+        impl PropertyObserver for Observer {
+            fn on_color_property(&mut self, _node_name: &str, _handle: ColorPropertyHandle) {
+                panic!("observer panicked")
+            }
+        }
+
+        Builder::default()
+            .set_property_observer(&mut Observer)
+            .make(BOX.as_bytes());
+    }
+
+    fn center_pixel(surface: &mut Surface) -> Color {
+        let mut pixel = [0u8; 4];
+        let info = surface.image_info().with_dimensions((1, 1));
+        assert!(surface.read_pixels(&info, &mut pixel, 4, (5, 5)));
+        Color::new(u32::from_ne_bytes(pixel))
+    }
+}
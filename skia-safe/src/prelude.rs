@@ -2,10 +2,13 @@ use skia_bindings::{
     C_SkRefCntBase_ref, C_SkRefCntBase_unique, C_SkRefCntBase_unref, SkNVRefCnt, SkRefCnt,
     SkRefCntBase,
 };
+use std::any::Any;
+use std::cell::RefCell;
 use std::hash::{Hash, Hasher};
 use std::mem::MaybeUninit;
 use std::ops::{Deref, DerefMut, Index, IndexMut};
-use std::{mem, ptr, slice};
+use std::panic::{self, AssertUnwindSafe};
+use std::{mem, process, ptr, slice};
 // Re-export TryFrom / TryInto to make them available in all modules that use prelude::*.
 pub use std::convert::{TryFrom, TryInto};
 use std::marker::PhantomData;
@@ -298,6 +301,36 @@ pub(crate) fn construct<N>(construct: impl FnOnce(*mut N)) -> N {
     unsafe { instance.assume_init() }
 }
 
+thread_local! {
+    /// The first panic of a callback that is deferred until control returns to Rust.
+    static CALLBACK_PANIC: RefCell<Option<Box<dyn Any + Send>>> = RefCell::new(None);
+}
+
+/// Calls `f` from a callback that is invoked by Skia. Unwinding through C++ is undefined
+/// behavior, so a panic is caught, `default` is returned and the panic is resumed by
+/// [`resume_callback_panic()`] after the native call returns.
+pub(crate) fn catch_callback_panic<R>(default: R, f: impl FnOnce() -> R) -> R {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        CALLBACK_PANIC.with(|panic| {
+            panic.borrow_mut().get_or_insert(payload);
+        });
+        default
+    })
+}
+
+/// Resumes the panic that was caught by [`catch_callback_panic()`], if any.
+pub(crate) fn resume_callback_panic() {
+    if let Some(payload) = CALLBACK_PANIC.with(|panic| panic.borrow_mut().take()) {
+        panic::resume_unwind(payload)
+    }
+}
+
+/// Calls `f` from a callback that is invoked by Skia at a time where there is no native call
+/// to resume a panic after, so the process is aborted if `f` panics.
+pub(crate) fn abort_on_callback_panic<R>(f: impl FnOnce() -> R) -> R {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| process::abort())
+}
+
 impl<N: NativeDrop> Drop for Handle<N> {
     fn drop(&mut self) {
         self.0.drop()
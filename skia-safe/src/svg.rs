@@ -1,2 +1,7 @@
 pub mod canvas;
-pub use self::canvas::Canvas;
+pub use self::canvas::{Canvas, WriterCanvas};
+
+#[cfg(feature = "svg")]
+mod dom;
+#[cfg(feature = "svg")]
+pub use self::dom::*;
@@ -1,8 +1,9 @@
-use crate::interop::DynamicMemoryWStream;
+use crate::interop::{DynamicMemoryWStream, RustWStream};
 use crate::prelude::*;
 use crate::{Data, Rect};
 use skia_bindings as sb;
 use skia_bindings::SkCanvas;
+use std::io;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 use std::ptr;
@@ -35,6 +36,8 @@ impl DerefMut for Canvas {
 }
 
 bitflags! {
+    /// The options of the SVG canvas, these are all the flags `SkSVGCanvas` supports in this
+    /// version of Skia.
     #[derive(Default)]
     pub struct Flags : u32 {
         const CONVERT_TEXT_TO_PATHS = sb::SkSVGCanvas_kConvertTextToPaths_Flag as _;
@@ -72,6 +75,83 @@ impl Canvas {
     }
 }
 
+/// A SVG canvas that writes the SVG document to a [`Write`](io::Write) target while it is drawn.
+///
+/// Created by [`Canvas::from_writer()`].
+pub struct WriterCanvas<'a> {
+    canvas: *mut SkCanvas,
+    stream: RustWStream<'a>,
+}
+
+impl Drop for WriterCanvas<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            sb::C_SkCanvas_delete(self.canvas);
+        }
+    }
+}
+
+impl Deref for WriterCanvas<'_> {
+    type Target = crate::Canvas;
+
+    fn deref(&self) -> &Self::Target {
+        crate::Canvas::borrow_from_native(unsafe { &mut *self.canvas })
+    }
+}
+
+impl DerefMut for WriterCanvas<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        crate::Canvas::borrow_from_native(unsafe { &mut *self.canvas })
+    }
+}
+
+impl Canvas {
+    /// Creates a new SVG canvas that writes the SVG document incrementally to `writer`.
+    ///
+    /// The document is completed by [`WriterCanvas::end()`], or when the canvas is dropped, in
+    /// which case write errors are ignored.
+    pub fn from_writer<'a>(
+        bounds: impl AsRef<Rect>,
+        writer: impl io::Write + 'a,
+        flags: impl Into<Option<Flags>>,
+    ) -> WriterCanvas<'a> {
+        let bounds = bounds.as_ref();
+        let flags = flags.into().unwrap_or_default();
+        let mut stream = RustWStream::new(writer);
+        let canvas =
+            unsafe { sb::C_SkSVGCanvas_Make(bounds.native(), stream.native_mut(), flags.bits()) };
+        WriterCanvas { canvas, stream }
+    }
+}
+
+impl WriterCanvas<'_> {
+    /// Ends the drawing, writes the remaining SVG to the writer and flushes it.
+    ///
+    /// Returns the first error that occurred while writing.
+    pub fn end(mut self) -> io::Result<()> {
+        unsafe {
+            sb::C_SkCanvas_delete(self.canvas);
+        }
+        self.canvas = ptr::null_mut();
+        self.stream.flush()
+    }
+}
+
+#[test]
+fn test_svg_to_writer() {
+    use crate::Paint;
+
+    let mut svg = Vec::new();
+    let mut canvas =
+        Canvas::from_writer(&Rect::from_size((20, 20)), &mut svg, Flags::NO_PRETTY_XML);
+    canvas.draw_circle((10, 10), 10.0, &Paint::default());
+    canvas.end().unwrap();
+
+    let contents = String::from_utf8_lossy(&svg);
+    assert!(contents.contains(r#"<ellipse cx="10" cy="10" rx="10" ry="10"/>"#));
+    assert!(contents.trim_end().ends_with("</svg>"));
+}
+
 #[test]
 fn test_svg() {
     use crate::Paint;
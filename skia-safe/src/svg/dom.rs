@@ -0,0 +1,84 @@
+use crate::interop::{MemoryStream, NativeStreamBase, RustStream};
+use crate::prelude::*;
+use crate::{Canvas, Size};
+use skia_bindings as sb;
+use skia_bindings::{SkRefCntBase, SkSVGDOM};
+use std::io;
+
+/// The document object model of a SVG document, that can be rendered to a [`Canvas`].
+///
+/// `<text>` and `<image>` elements are not supported by the SVG DOM of this Skia version and
+/// are not rendered, so there are no hooks to provide a font manager or external resources.
+pub type Dom = RCHandle<SkSVGDOM>;
+
+impl NativeRefCountedBase for SkSVGDOM {
+    type Base = SkRefCntBase;
+}
+
+impl RCHandle<SkSVGDOM> {
+    /// Parses a SVG document, returns `None` if `bytes` do not contain valid XML.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Dom> {
+        let mut stream = MemoryStream::from_bytes(bytes);
+        Dom::from_ptr(unsafe { sb::C_SkSVGDOM_MakeFromStream(stream.native_mut().as_stream_mut()) })
+    }
+
+    /// Reads and parses a SVG document from `stream`.
+    pub fn from_stream(stream: impl io::Read + io::Seek) -> io::Result<Dom> {
+        let mut stream = RustStream::new(stream)?;
+        let dom = Dom::from_ptr(unsafe { sb::C_SkSVGDOM_MakeFromStream(stream.as_stream_mut()) });
+        let dom = stream.error().clone().into_result(dom)?;
+        dom.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid SVG document"))
+    }
+
+    /// The size of the viewport the document is rendered into.
+    ///
+    /// Relative lengths of the root `<svg>` element, for example a width of `100%`, are resolved
+    /// against the container size.
+    pub fn container_size(&self) -> Size {
+        let mut size = Size::default();
+        unsafe { sb::C_SkSVGDOM_containerSize(self.native(), size.native_mut()) };
+        size
+    }
+
+    pub fn set_container_size(&mut self, size: impl Into<Size>) -> &mut Self {
+        unsafe { sb::C_SkSVGDOM_setContainerSize(self.native_mut(), size.into().native()) }
+        self
+    }
+
+    /// Renders the document to `canvas`.
+    pub fn render(&self, canvas: &mut Canvas) {
+        unsafe { sb::C_SkSVGDOM_render(self.native(), canvas.native_mut()) }
+    }
+}
+
+#[test]
+fn render_svg_document() {
+    use crate::{Color, Surface};
+
+    let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="100%" height="100%">
+        <rect x="0" y="0" width="5" height="5" fill="#ff0000"/>
+    </svg>"##;
+
+    let mut dom = Dom::from_bytes(svg).unwrap();
+    dom.set_container_size((10, 10));
+    assert_eq!(dom.container_size(), Size::new(10.0, 10.0));
+
+    let mut surface = Surface::new_raster_n32_premul((10, 10)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    dom.render(surface.canvas());
+
+    let image = surface.image_snapshot();
+    let mut pixels = [0u32; 100];
+    let info = image.image_info().clone();
+    assert!(image.read_pixels(
+        &info,
+        &mut pixels,
+        info.min_row_bytes(),
+        (0, 0),
+        crate::image::CachingHint::Allow
+    ));
+    assert_eq!(Color::new(pixels[0]), Color::RED);
+    assert_eq!(Color::new(pixels[99]), Color::WHITE);
+
+    assert!(Dom::from_stream(io::Cursor::new(&b"<svg"[..])).is_err());
+}
@@ -10,6 +10,12 @@ pub use interpolator::Interpolator;
 mod null_canvas;
 pub use null_canvas::*;
 
+mod nway_canvas;
+pub use nway_canvas::*;
+
+mod paint_filter_canvas;
+pub use paint_filter_canvas::*;
+
 pub mod parse_path;
 pub mod shadow_utils;
 pub mod text_utils;
@@ -0,0 +1,110 @@
+use crate::prelude::*;
+use crate::{Canvas, ISize, OwnedCanvas};
+use skia_bindings as sb;
+use skia_bindings::SkCanvas;
+use std::ops::{Deref, DerefMut};
+
+/// A canvas that forwards every save, matrix, clip and draw call to a list of canvases.
+///
+/// The canvas has the dimensions it is created with, but does not have pixels of its own.
+pub struct NWayCanvas<'lt>(OwnedCanvas<'lt>);
+
+/// Identifies a canvas that was added to a [`NWayCanvas`], so that it can be removed again.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct NWayCanvasEntry(*mut SkCanvas);
+
+impl<'lt> Deref for NWayCanvas<'lt> {
+    type Target = Canvas;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'lt> DerefMut for NWayCanvas<'lt> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'lt> AsMut<Canvas> for NWayCanvas<'lt> {
+    fn as_mut(&mut self) -> &mut Canvas {
+        self.deref_mut()
+    }
+}
+
+impl<'lt> NWayCanvas<'lt> {
+    pub fn new(size: impl Into<ISize>) -> NWayCanvas<'lt> {
+        let size = size.into();
+        let ptr = unsafe { sb::C_SkNWayCanvas_new(size.width, size.height) };
+        NWayCanvas(Canvas::own_from_native_ptr(ptr).unwrap())
+    }
+
+    /// Adds `canvas` to the canvases the calls are forwarded to.
+    ///
+    /// Only calls made after `canvas` was added are forwarded, so the matrix and clip of
+    /// `canvas` may differ from the other canvases if it is added while the matrix or clip of
+    /// this canvas is not the default.
+    ///
+    /// Returns the entry that removes `canvas` with [`remove_canvas()`](Self::remove_canvas).
+    pub fn add_canvas(&mut self, canvas: &'lt mut Canvas) -> NWayCanvasEntry {
+        let canvas = canvas.native_mut() as *mut SkCanvas;
+        unsafe { sb::C_SkNWayCanvas_addCanvas(self.native_mut(), canvas) }
+        NWayCanvasEntry(canvas)
+    }
+
+    /// Stops forwarding the calls to the canvas that was added as `entry`.
+    ///
+    /// The canvas stays borrowed for the lifetime of this canvas.
+    pub fn remove_canvas(&mut self, entry: NWayCanvasEntry) -> &mut Self {
+        unsafe { sb::C_SkNWayCanvas_removeCanvas(self.native_mut(), entry.0) }
+        self
+    }
+
+    pub fn remove_all(&mut self) -> &mut Self {
+        unsafe { sb::C_SkNWayCanvas_removeAll(self.native_mut()) }
+        self
+    }
+}
+
+#[test]
+fn nway_canvas_draws_to_all_canvases() {
+    use crate::{Color, Surface};
+
+    let mut surface1 = Surface::new_raster_n32_premul((2, 2)).unwrap();
+    let mut surface2 = Surface::new_raster_n32_premul((2, 2)).unwrap();
+    {
+        let mut canvas = NWayCanvas::new((2, 2));
+        canvas.add_canvas(surface1.canvas());
+        canvas.add_canvas(surface2.canvas());
+        canvas.clear(Color::RED);
+    }
+    for surface in &mut [surface1, surface2] {
+        let pixmap = surface.peek_pixels().unwrap();
+        assert_eq!(pixmap.get_color((1, 1)), Color::RED);
+    }
+}
+
+#[test]
+fn nway_canvas_stops_drawing_to_removed_canvases() {
+    use crate::{Color, Surface};
+
+    let mut surface1 = Surface::new_raster_n32_premul((2, 2)).unwrap();
+    let mut surface2 = Surface::new_raster_n32_premul((2, 2)).unwrap();
+    {
+        let mut canvas = NWayCanvas::new((2, 2));
+        canvas.add_canvas(surface1.canvas());
+        let entry = canvas.add_canvas(surface2.canvas());
+        canvas.clear(Color::RED);
+        canvas.remove_canvas(entry);
+        canvas.clear(Color::BLUE);
+    }
+    assert_eq!(
+        surface1.peek_pixels().unwrap().get_color((1, 1)),
+        Color::BLUE
+    );
+    assert_eq!(
+        surface2.peek_pixels().unwrap().get_color((1, 1)),
+        Color::RED
+    );
+}
@@ -0,0 +1,87 @@
+use crate::prelude::*;
+use crate::{Canvas, OwnedCanvas, Paint};
+use skia_bindings as sb;
+use skia_bindings::SkPaint;
+use std::ffi::c_void;
+use std::ops::{Deref, DerefMut};
+
+/// A canvas that forwards its calls to another canvas, after passing every paint through a
+/// filter.
+pub struct PaintFilterCanvas<'lt>(OwnedCanvas<'lt>);
+
+type PaintFilter<'lt> = Box<dyn FnMut(&mut Paint) -> bool + 'lt>;
+
+impl<'lt> Deref for PaintFilterCanvas<'lt> {
+    type Target = Canvas;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'lt> DerefMut for PaintFilterCanvas<'lt> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'lt> AsMut<Canvas> for PaintFilterCanvas<'lt> {
+    fn as_mut(&mut self) -> &mut Canvas {
+        self.deref_mut()
+    }
+}
+
+impl<'lt> PaintFilterCanvas<'lt> {
+    /// Creates a canvas that draws to `canvas` with the paints modified by `filter`.
+    ///
+    /// `filter` is called with a copy of the paint of every draw call, and may modify it. If it
+    /// returns `false`, the draw call is skipped. If it panics, the process is aborted.
+    pub fn new(
+        canvas: &'lt mut Canvas,
+        filter: impl FnMut(&mut Paint) -> bool + 'lt,
+    ) -> PaintFilterCanvas<'lt> {
+        let filter: Box<PaintFilter<'lt>> = Box::new(Box::new(filter));
+        let ptr = unsafe {
+            sb::C_RustPaintFilterCanvas_new(
+                canvas.native_mut(),
+                Some(filter_paint),
+                Some(drop_filter),
+                Box::into_raw(filter) as _,
+            )
+        };
+        PaintFilterCanvas(Canvas::own_from_native_ptr(ptr).unwrap())
+    }
+}
+
+unsafe extern "C" fn filter_paint(context: *mut c_void, paint: *mut SkPaint) -> bool {
+    abort_on_callback_panic(|| {
+        let filter = &mut *(context as *mut PaintFilter);
+        filter(Paint::from_native_ref_mut(&mut *paint))
+    })
+}
+
+unsafe extern "C" fn drop_filter(context: *mut c_void) {
+    abort_on_callback_panic(|| drop(Box::from_raw(context as *mut PaintFilter)))
+}
+
+#[test]
+fn paint_filter_canvas_filters_paints() {
+    use crate::{Color, Rect, Surface};
+
+    let mut surface = Surface::new_raster_n32_premul((4, 4)).unwrap();
+    surface.canvas().clear(Color::WHITE);
+    {
+        let mut canvas = PaintFilterCanvas::new(surface.canvas(), |paint| {
+            paint.set_color(Color::BLUE);
+            !paint.is_anti_alias()
+        });
+        let mut paint = Paint::default();
+        paint.set_color(Color::RED);
+        canvas.draw_rect(Rect::from_wh(2.0, 2.0), &paint);
+        paint.set_anti_alias(true);
+        canvas.draw_rect(Rect::new(2.0, 2.0, 4.0, 4.0), &paint);
+    }
+    let pixmap = surface.peek_pixels().unwrap();
+    assert_eq!(pixmap.get_color((0, 0)), Color::BLUE);
+    assert_eq!(pixmap.get_color((3, 3)), Color::WHITE);
+}
@@ -12,6 +12,21 @@ bitflags! {
     }
 }
 
+/// The parameters of a shadow as a canvas receives them, see `SkDrawShadowRec`.
+///
+/// [`draw_shadow()`] passes its parameters on to the canvas, which may adjust them before they
+/// are forwarded to [`CanvasImpl::on_draw_shadow_rec()`](crate::CanvasImpl::on_draw_shadow_rec),
+/// so a shadow record can't be drawn again with [`draw_shadow()`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DrawShadowRec {
+    pub z_plane_params: Point3,
+    pub light_pos: Point3,
+    pub light_radius: scalar,
+    pub ambient_color: Color,
+    pub spot_color: Color,
+    pub flags: ShadowFlags,
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn draw_shadow(
     mut canvas: impl AsMut<Canvas>,